@@ -1,4 +1,5 @@
 use std::{
+    collections::{HashMap, VecDeque},
     ffi::{
         OsStr,
         OsString,
@@ -19,6 +20,13 @@ use crate::watches::WatchDescriptor;
 /// Allows for iteration over the events returned by
 /// [`Inotify::read_events_blocking`] or [`Inotify::read_events`].
 ///
+/// `buffer[..num_bytes]` must contain only complete events; this holds for
+/// both of those constructors, since the kernel never splits a record across
+/// two `read`s. If you're instead feeding this from a raw byte stream that
+/// can end partway through a record (e.g. a socket), don't use `Events` --
+/// parse one event at a time with [`Event::try_from_buffer`], and prepend any
+/// unconsumed trailing bytes to the next chunk you read before parsing again.
+///
 /// [`Inotify::read_events_blocking`]: crate::Inotify::read_events_blocking
 /// [`Inotify::read_events`]: crate::Inotify::read_events
 #[derive(Debug)]
@@ -42,6 +50,38 @@ impl<'a> Events<'a> {
     }
 }
 
+impl<'a> Events<'a> {
+    /// Create an `Events` iterator directly from a buffer of complete events
+    ///
+    /// Unlike the iterators returned by [`Inotify::read_events_blocking`]/
+    /// [`Inotify::read_events`], the [`WatchDescriptor`]s this yields aren't
+    /// tied to a live inotify file descriptor, so [`Watches::remove`] can't
+    /// be called with them. This is meant for parsing a buffer of raw
+    /// inotify event bytes obtained independently of a live [`Inotify`]
+    /// instance, e.g. replayed from a log.
+    ///
+    /// `buffer` must contain only complete events; see the type-level docs
+    /// for what to do if that doesn't hold for your use case.
+    ///
+    /// [`Inotify`]: crate::Inotify
+    /// [`Inotify::read_events_blocking`]: crate::Inotify::read_events_blocking
+    /// [`Inotify::read_events`]: crate::Inotify::read_events
+    /// [`Watches::remove`]: crate::Watches::remove
+    #[must_use]
+    pub fn from_buffer(buffer: &'a [u8]) -> Self {
+        let num_bytes = buffer.len();
+        Events::new(Weak::new(), buffer, num_bytes)
+    }
+
+    /// Adapts this iterator into one that correlates renames
+    ///
+    /// See [`CookedEvents`] for details.
+    #[must_use]
+    pub fn cooked(self) -> CookedEvents<'a> {
+        CookedEvents::new(self)
+    }
+}
+
 impl<'a> Iterator for Events<'a> {
     type Item = Event<&'a OsStr>;
 
@@ -59,6 +99,135 @@ impl<'a> Iterator for Events<'a> {
 }
 
 
+/// Iterator over events, with `MOVED_FROM`/`MOVED_TO` pairs correlated
+///
+/// Created by [`Events::cooked`]. A file rename within watched directories
+/// shows up from the kernel as two separate events, [`MOVED_FROM`] and
+/// [`MOVED_TO`], sharing the same `cookie`. This iterator buffers `MOVED_FROM`
+/// events by their cookie and, once the matching `MOVED_TO` arrives, yields a
+/// single [`CookedEvent::Moved`] in its place.
+///
+/// Since the matching half of a pair isn't guaranteed to arrive in the same
+/// read (the file may have been moved out of, or into, the watched tree
+/// entirely), any `MOVED_FROM` left unmatched once the underlying [`Events`]
+/// is exhausted is flushed as [`CookedEvent::MovedOut`]; an umatched
+/// `MOVED_TO` is yielded immediately as [`CookedEvent::MovedIn`], since
+/// nothing later in the batch could still complete it. All other events pass
+/// through unchanged as [`CookedEvent::Plain`].
+///
+/// A [`Q_OVERFLOW`] event discards any events that were dropped by the
+/// kernel along with it, so any `MOVED_FROM` events still pending at that
+/// point can no longer be assumed to have a matching `MOVED_TO` coming; they
+/// are flushed as [`CookedEvent::MovedOut`] before the overflow event itself
+/// is yielded.
+///
+/// [`MOVED_FROM`]: EventMask::MOVED_FROM
+/// [`MOVED_TO`]: EventMask::MOVED_TO
+/// [`Q_OVERFLOW`]: EventMask::Q_OVERFLOW
+#[derive(Debug)]
+pub struct CookedEvents<'a> {
+    inner       : Events<'a>,
+    pending_from: HashMap<u32, Event<&'a OsStr>>,
+    ready       : VecDeque<CookedEvent<&'a OsStr>>,
+}
+
+impl<'a> CookedEvents<'a> {
+    fn new(inner: Events<'a>) -> Self {
+        CookedEvents {
+            inner,
+            pending_from: HashMap::new(),
+            ready: VecDeque::new(),
+        }
+    }
+
+    fn flush_pending_from(&mut self) {
+        for (_, event) in self.pending_from.drain() {
+            self.ready.push_back(CookedEvent::MovedOut(event));
+        }
+    }
+}
+
+impl<'a> Iterator for CookedEvents<'a> {
+    type Item = CookedEvent<&'a OsStr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.ready.pop_front() {
+                return Some(event);
+            }
+
+            let event = match self.inner.next() {
+                Some(event) => event,
+                None => {
+                    if self.pending_from.is_empty() {
+                        return None;
+                    }
+                    self.flush_pending_from();
+                    continue;
+                }
+            };
+
+            if event.mask.contains(EventMask::Q_OVERFLOW) {
+                self.flush_pending_from();
+                self.ready.push_back(CookedEvent::Plain(event));
+            }
+            else if event.mask.contains(EventMask::MOVED_FROM) {
+                self.pending_from.insert(event.cookie, event);
+            }
+            else if event.mask.contains(EventMask::MOVED_TO) {
+                match self.pending_from.remove(&event.cookie) {
+                    Some(from) => self.ready.push_back(CookedEvent::Moved {
+                        from_wd: from.wd,
+                        from_name: from.name,
+                        to_wd: event.wd,
+                        to_name: event.name,
+                    }),
+                    None => self.ready.push_back(CookedEvent::MovedIn(event)),
+                }
+            }
+            else {
+                self.ready.push_back(CookedEvent::Plain(event));
+            }
+        }
+    }
+}
+
+
+/// An event yielded by [`CookedEvents`]
+///
+/// See [`CookedEvents`] for the rules governing which variant a given raw
+/// event ends up as.
+#[derive(Clone, Debug)]
+pub enum CookedEvent<S> {
+    /// An event that isn't part of a rename pair
+    Plain(Event<S>),
+
+    /// A file was renamed/moved, correlating a `MOVED_FROM`/`MOVED_TO` pair
+    Moved {
+        /// The watch and previous name the file was moved from
+        from_wd: WatchDescriptor,
+        /// See [`Event::name`]
+        from_name: Option<S>,
+        /// The watch and new name the file was moved to
+        to_wd: WatchDescriptor,
+        /// See [`Event::name`]
+        to_name: Option<S>,
+    },
+
+    /// A `MOVED_FROM` event whose matching `MOVED_TO` never arrived
+    ///
+    /// This happens when a file is moved out of the watched directory tree,
+    /// to a location that isn't watched.
+    MovedOut(Event<S>),
+
+    /// A `MOVED_TO` event whose matching `MOVED_FROM` never arrived
+    ///
+    /// This happens when a file is moved into the watched directory tree
+    /// from a location that wasn't watched.
+    MovedIn(Event<S>),
+}
+
+
 /// An inotify event
 ///
 /// A file system event that describes a change that the user previously
@@ -108,8 +277,9 @@ impl<'a> Event<&'a OsStr> {
     fn new(fd: Weak<FdGuard>, event: &ffi::inotify_event, name: &'a OsStr)
         -> Self
     {
-        let mask = EventMask::from_bits(event.mask)
-            .expect("Failed to convert event mask. This indicates a bug.");
+        // Retain any bits the kernel sets that this version of the crate
+        // doesn't know about yet, rather than panicking on them.
+        let mask = EventMask::from_bits_retain(event.mask);
 
         let wd = crate::WatchDescriptor {
             id: event.wd,
@@ -134,7 +304,9 @@ impl<'a> Event<&'a OsStr> {
     /// Create an `Event` from a buffer
     ///
     /// Assumes that a full `inotify_event` plus its name is located at the
-    /// beginning of `buffer`.
+    /// beginning of `buffer`. This is the infallible counterpart to
+    /// [`Event::try_from_buffer`], used internally by [`Events`], whose
+    /// `buffer[..num_bytes]` is guaranteed to contain only complete events.
     ///
     /// Returns the number of bytes used from the buffer, and the event.
     ///
@@ -146,17 +318,40 @@ impl<'a> Event<&'a OsStr> {
         buffer: &'a [u8],
     )
         -> (usize, Self)
+    {
+        Self::try_from_buffer(fd, buffer)
+            .expect("buffer passed to `Events` should only ever contain complete events")
+    }
+
+    /// Create an `Event` from a buffer, without assuming it holds a full event
+    ///
+    /// Returns the number of bytes used from the buffer, and the event. If
+    /// `buffer` ends before a full `inotify_event` plus its name, returns
+    /// `None` instead of panicking -- the caller should read more data,
+    /// prepend it to whatever of `buffer` it didn't consume, and try again.
+    /// This makes it suitable for driving inotify parsing off of raw reads
+    /// from a source that can return in the middle of a record, unlike
+    /// `Events`, which assumes the kernel's read-never-splits-a-record
+    /// guarantee.
+    #[must_use]
+    pub fn try_from_buffer(
+        fd    : Weak<FdGuard>,
+        buffer: &'a [u8],
+    )
+        -> Option<(usize, Self)>
     {
         let event_size = mem::size_of::<ffi::inotify_event>();
 
         // Make sure that the buffer is big enough to contain an event, without
         // the name. Otherwise we can't safely convert it to an `inotify_event`.
-        assert!(buffer.len() >= event_size);
+        if buffer.len() < event_size {
+            return None;
+        }
 
         let ffi_event_ptr = buffer.as_ptr() as *const ffi::inotify_event;
 
         // We have a pointer to an `inotify_event`, pointing to the beginning of
-        // `buffer`. Since we know, as per the assertion above, that there are
+        // `buffer`. Since we know, as per the check above, that there are
         // enough bytes in the buffer for at least one event, we can safely
         // read that `inotify_event`.
         // We call `read_unaligned()` since the byte buffer has alignment 1
@@ -165,10 +360,13 @@ impl<'a> Event<&'a OsStr> {
         let ffi_event = unsafe { ffi_event_ptr.read_unaligned() };
 
         // The name's length is given by `event.len`. There should always be
-        // enough bytes left in the buffer to fit the name. Let's make sure that
-        // is the case.
+        // enough bytes left in the buffer to fit the name; if there aren't
+        // yet, the buffer ends partway through this record and the caller
+        // needs to read more before it can be parsed.
         let bytes_left_in_buffer = buffer.len() - event_size;
-        assert!(bytes_left_in_buffer >= ffi_event.len as usize);
+        if bytes_left_in_buffer < ffi_event.len as usize {
+            return None;
+        }
 
         // Directly after the event struct should be a name, if there's one
         // associated with the event. Let's make a new slice that starts with
@@ -195,7 +393,7 @@ impl<'a> Event<&'a OsStr> {
             OsStr::from_bytes(name),
         );
 
-        (bytes_consumed, event)
+        Some((bytes_consumed, event))
     }
 
     /// Returns an owned copy of the event.
@@ -222,6 +420,183 @@ impl<'a> Event<&'a OsStr> {
 pub type EventOwned = Event<OsString>;
 
 
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use std::{
+        ffi::OsString,
+        fmt,
+        os::unix::ffi::OsStrExt,
+    };
+
+    use serde::{
+        de::{self, Deserializer, SeqAccess, Visitor},
+        ser::{SerializeSeq, SerializeStruct, Serializer},
+        Deserialize, Serialize,
+    };
+
+    use super::{Event, EventMask, EventOwned};
+
+    /// `(flag, name)` pairs for every individual flag `EventMask` can hold
+    ///
+    /// Used to serialize an `EventMask` as a stable list of names rather than
+    /// its raw bits, so the representation doesn't depend on the host's
+    /// `inotify_sys` constant values.
+    const FLAGS: &[(EventMask, &str)] = &[
+        (EventMask::ACCESS, "ACCESS"),
+        (EventMask::ATTRIB, "ATTRIB"),
+        (EventMask::CLOSE_WRITE, "CLOSE_WRITE"),
+        (EventMask::CLOSE_NOWRITE, "CLOSE_NOWRITE"),
+        (EventMask::CREATE, "CREATE"),
+        (EventMask::DELETE, "DELETE"),
+        (EventMask::DELETE_SELF, "DELETE_SELF"),
+        (EventMask::MODIFY, "MODIFY"),
+        (EventMask::MOVE_SELF, "MOVE_SELF"),
+        (EventMask::MOVED_FROM, "MOVED_FROM"),
+        (EventMask::MOVED_TO, "MOVED_TO"),
+        (EventMask::OPEN, "OPEN"),
+        (EventMask::IGNORED, "IGNORED"),
+        (EventMask::ISDIR, "ISDIR"),
+        (EventMask::Q_OVERFLOW, "Q_OVERFLOW"),
+        (EventMask::UNMOUNT, "UNMOUNT"),
+    ];
+
+    const FLAG_NAMES: &[&str] = &[
+        "ACCESS", "ATTRIB", "CLOSE_WRITE", "CLOSE_NOWRITE", "CREATE", "DELETE",
+        "DELETE_SELF", "MODIFY", "MOVE_SELF", "MOVED_FROM", "MOVED_TO", "OPEN",
+        "IGNORED", "ISDIR", "Q_OVERFLOW", "UNMOUNT",
+    ];
+
+    impl Serialize for EventMask {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let names: Vec<&str> = FLAGS
+                .iter()
+                .filter(|(flag, _)| self.contains(*flag))
+                .map(|(_, name)| *name)
+                .collect();
+
+            let mut seq = serializer.serialize_seq(Some(names.len()))?;
+            for name in &names {
+                seq.serialize_element(name)?;
+            }
+            seq.end()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for EventMask {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct MaskVisitor;
+
+            impl<'de> Visitor<'de> for MaskVisitor {
+                type Value = EventMask;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("a sequence of inotify event mask flag names")
+                }
+
+                fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                where
+                    A: SeqAccess<'de>,
+                {
+                    let mut mask = EventMask::empty();
+                    while let Some(name) = seq.next_element::<String>()? {
+                        let flag = FLAGS
+                            .iter()
+                            .find(|(_, candidate)| *candidate == name)
+                            .map(|(flag, _)| *flag)
+                            .ok_or_else(|| de::Error::unknown_variant(&name, FLAG_NAMES))?;
+                        mask |= flag;
+                    }
+                    Ok(mask)
+                }
+            }
+
+            deserializer.deserialize_seq(MaskVisitor)
+        }
+    }
+
+    /// Encodes an `OsString` as UTF-8 where possible, falling back to its raw
+    /// bytes otherwise
+    ///
+    /// Most event names are valid UTF-8 and serialize as a plain string for
+    /// readability in human-facing formats (e.g. JSON); names that aren't
+    /// valid UTF-8 still round-trip losslessly via the byte fallback.
+    #[derive(Serialize, Deserialize)]
+    enum OsStringRepr {
+        Utf8(String),
+        Bytes(Vec<u8>),
+    }
+
+    impl From<&OsString> for OsStringRepr {
+        fn from(s: &OsString) -> Self {
+            match s.to_str() {
+                Some(s) => OsStringRepr::Utf8(s.to_owned()),
+                None => OsStringRepr::Bytes(s.as_bytes().to_vec()),
+            }
+        }
+    }
+
+    impl From<OsStringRepr> for OsString {
+        fn from(repr: OsStringRepr) -> Self {
+            match repr {
+                OsStringRepr::Utf8(s) => OsString::from(s),
+                OsStringRepr::Bytes(bytes) => {
+                    std::os::unix::ffi::OsStringExt::from_vec(bytes)
+                }
+            }
+        }
+    }
+
+    impl Serialize for EventOwned {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let mut s = serializer.serialize_struct("Event", 4)?;
+            s.serialize_field("wd", &self.wd.id)?;
+            s.serialize_field("mask", &self.mask)?;
+            s.serialize_field("cookie", &self.cookie)?;
+            s.serialize_field("name", &self.name.as_ref().map(OsStringRepr::from))?;
+            s.end()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for EventOwned {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            #[derive(Deserialize)]
+            struct Raw {
+                wd: i32,
+                mask: EventMask,
+                cookie: u32,
+                name: Option<OsStringRepr>,
+            }
+
+            let raw = Raw::deserialize(deserializer)?;
+            Ok(Event {
+                // The original `Weak<FdGuard>` can't be recovered from
+                // serialized data; a deserialized watch descriptor is only
+                // useful for comparison/display, not for removing the watch.
+                wd: crate::WatchDescriptor {
+                    id: raw.wd,
+                    fd: std::sync::Weak::new(),
+                },
+                mask: raw.mask,
+                cookie: raw.cookie,
+                name: raw.name.map(OsString::from),
+            })
+        }
+    }
+}
+
+
 bitflags! {
     /// Indicates the type of an event
     ///
@@ -354,6 +729,41 @@ bitflags! {
         ///
         /// See [`inotify_sys::IN_UNMOUNT`].
         const UNMOUNT = ffi::IN_UNMOUNT;
+
+        /// File opened for writing was closed, or one not opened for writing was
+        ///
+        /// A convenience combination of [`CLOSE_WRITE`] and [`CLOSE_NOWRITE`].
+        ///
+        /// [`CLOSE_WRITE`]: EventMask::CLOSE_WRITE
+        /// [`CLOSE_NOWRITE`]: EventMask::CLOSE_NOWRITE
+        ///
+        /// See [`inotify_sys::IN_CLOSE`].
+        const CLOSE = ffi::IN_CLOSE;
+
+        /// File was renamed/moved, in either direction
+        ///
+        /// A convenience combination of [`MOVED_FROM`] and [`MOVED_TO`].
+        ///
+        /// [`MOVED_FROM`]: EventMask::MOVED_FROM
+        /// [`MOVED_TO`]: EventMask::MOVED_TO
+        ///
+        /// See [`inotify_sys::IN_MOVE`].
+        const MOVE = ffi::IN_MOVE;
+
+        /// Any event that can be reported by inotify, excluding the
+        /// status-only flags ([`IGNORED`], [`ISDIR`], [`Q_OVERFLOW`],
+        /// [`UNMOUNT`])
+        ///
+        /// Mainly useful when registering a watch for every kind of file
+        /// event.
+        ///
+        /// [`IGNORED`]: EventMask::IGNORED
+        /// [`ISDIR`]: EventMask::ISDIR
+        /// [`Q_OVERFLOW`]: EventMask::Q_OVERFLOW
+        /// [`UNMOUNT`]: EventMask::UNMOUNT
+        ///
+        /// See [`inotify_sys::IN_ALL_EVENTS`].
+        const ALL_EVENTS = ffi::IN_ALL_EVENTS;
     }
 }
 
@@ -368,6 +778,31 @@ impl EventMask {
     pub unsafe fn from_bits_unchecked(bits: u32) -> Self {
         Self::from_bits_retain(bits)
     }
+
+    /// Returns `true` if the event's subject is a directory
+    ///
+    /// A convenience wrapper around checking [`EventMask::ISDIR`].
+    #[must_use]
+    pub fn is_dir(&self) -> bool {
+        self.contains(EventMask::ISDIR)
+    }
+
+    /// Returns `true` if the event queue has overflowed
+    ///
+    /// A convenience wrapper around checking [`EventMask::Q_OVERFLOW`].
+    #[must_use]
+    pub fn is_overflow(&self) -> bool {
+        self.contains(EventMask::Q_OVERFLOW)
+    }
+
+    /// Returns `true` if this is one half of a rename/move pair
+    ///
+    /// A convenience wrapper around checking [`EventMask::MOVE`], i.e.
+    /// [`EventMask::MOVED_FROM`] or [`EventMask::MOVED_TO`].
+    #[must_use]
+    pub fn is_move(&self) -> bool {
+        self.intersects(EventMask::MOVE)
+    }
 }
 
 
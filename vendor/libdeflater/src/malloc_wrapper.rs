@@ -21,30 +21,337 @@
 //! `sizeof(usize)` back and get the original `size_and_data_ptr`.
 //! At this point we can read `size` back and call the Rust `dealloc`
 //! for the whole allocated chunk.
+//!
+//! With the `cache-align` feature enabled, a different layout is used
+//! instead (see the `aligned` module below) that over-aligns `data_ptr`
+//! to a 64-byte cache line / SIMD boundary, since libdeflate's
+//! compression/decompression hot loops benefit from that alignment the
+//! same way `posix_memalign(..., 64, ...)` does for zlib-rs.
 
 use libdeflate_sys::libdeflate_options;
-use std::alloc::*;
+use std::alloc::{GlobalAlloc, Layout};
 use std::ffi::c_void;
 use std::mem::{align_of, size_of};
+use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+
+/// Opt-in allocation statistics for libdeflate's memory use, gated behind
+/// the `stats` feature. Without it, the accounting below is completely
+/// invisible to callers -- it lives entirely inside this module's
+/// size-prefix trick -- which makes it hard to profile or bound a
+/// streaming/many-stream compressor's footprint.
+#[cfg(feature = "stats")]
+mod stats {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CURRENT_BYTES: AtomicUsize = AtomicUsize::new(0);
+    static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+    static ALLOC_COUNT: AtomicUsize = AtomicUsize::new(0);
+    static FREE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    /// A snapshot of [`memory_stats`], taken at some point in time.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct MemoryStats {
+        /// Bytes currently allocated through this crate's `malloc`/`free`
+        pub current_bytes: usize,
+        /// The highest `current_bytes` has ever reached
+        pub peak_bytes: usize,
+        /// Total number of successful `malloc` calls
+        pub alloc_count: usize,
+        /// Total number of `free` calls
+        pub free_count: usize,
+    }
+
+    pub(crate) fn record_alloc(size: usize) {
+        let current = CURRENT_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK_BYTES.fetch_max(current, Ordering::Relaxed);
+        ALLOC_COUNT.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_free(size: usize) {
+        CURRENT_BYTES.fetch_sub(size, Ordering::Relaxed);
+        FREE_COUNT.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Take a snapshot of libdeflate's current allocation statistics
+    #[must_use]
+    pub fn memory_stats() -> MemoryStats {
+        MemoryStats {
+            current_bytes: CURRENT_BYTES.load(Ordering::Relaxed),
+            peak_bytes: PEAK_BYTES.load(Ordering::Relaxed),
+            alloc_count: ALLOC_COUNT.load(Ordering::Relaxed),
+            free_count: FREE_COUNT.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Reset every allocation statistic back to zero
+    pub fn reset_memory_stats() {
+        CURRENT_BYTES.store(0, Ordering::Relaxed);
+        PEAK_BYTES.store(0, Ordering::Relaxed);
+        ALLOC_COUNT.store(0, Ordering::Relaxed);
+        FREE_COUNT.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "stats")]
+pub use stats::{memory_stats, reset_memory_stats, MemoryStats};
+
+#[cfg(not(feature = "cache-align"))]
+mod packed {
+    use std::alloc::*;
+    use std::ffi::c_void;
+    use std::mem::{align_of, size_of};
+
+    /// Returns `None` if `size_of::<usize>() + size` overflows or the
+    /// resulting layout is otherwise invalid, instead of the caller having
+    /// to re-check the addition itself -- `Layout::from_size_align` already
+    /// rejects a size that, rounded up to `align`, would overflow `isize`.
+    fn layout_for(size: usize) -> Option<Layout> {
+        let total_size = size_of::<usize>().checked_add(size)?;
+        Layout::from_size_align(total_size, align_of::<usize>()).ok()
+    }
 
-unsafe fn layout_for(size: usize) -> Layout {
-    Layout::from_size_align_unchecked(size_of::<usize>() + size, align_of::<usize>())
+    pub unsafe extern "C" fn malloc(size: usize) -> *mut c_void {
+        // libdeflate's C code expects `NULL` back on failure, not a panic
+        // or an abort, so every way this can fail has to be turned into a
+        // `null_mut()` return instead -- an overflowing size addition, a
+        // `Layout` the allocator itself would reject, or the allocator
+        // simply being out of memory.
+        let Some(layout) = layout_for(size) else {
+            return std::ptr::null_mut();
+        };
+
+        let size_and_data_ptr = alloc(layout);
+        if size_and_data_ptr.is_null() {
+            return std::ptr::null_mut();
+        }
+        *(size_and_data_ptr as *mut usize) = size;
+        #[cfg(feature = "stats")]
+        super::stats::record_alloc(size);
+        size_and_data_ptr.add(size_of::<usize>()) as _
+    }
+
+    pub unsafe extern "C" fn free(data_ptr: *mut c_void) {
+        let size_and_data_ptr = data_ptr.sub(size_of::<usize>());
+        let size = *(size_and_data_ptr as *const usize);
+        #[cfg(feature = "stats")]
+        super::stats::record_free(size);
+        // This `size` came from a layout that was successfully built (and
+        // allocated) by `malloc` above, so `layout_for` recomputing the same
+        // total can't fail here short of header corruption.
+        let layout = layout_for(size).expect("corrupted allocation header: size overflows layout");
+        dealloc(size_and_data_ptr as _, layout)
+    }
+}
+
+/// Cache-line aligned layout used when the `cache-align` feature is enabled.
+///
+/// `data_ptr` is always 64-byte aligned. Since the padding between the raw
+/// allocation (`base`) and `data_ptr` varies with wherever the global
+/// allocator happened to place `base`, it can't be recomputed from `size`
+/// alone the way the packed layout's offset can -- so it's stored
+/// alongside `size` in the two `usize`s immediately before `data_ptr`:
+///
+/// [...padding...][offset: `data_ptr - base`][size][...actual data...]
+/// -^---------------------------------------------------------------- `base`
+/// -------------------------------------------------^---------------- `data_ptr`
+#[cfg(feature = "cache-align")]
+mod aligned {
+    use std::alloc::*;
+    use std::ffi::c_void;
+    use std::mem::size_of;
+
+    const ALIGNMENT: usize = 64;
+    const HEADER_SIZE: usize = 2 * size_of::<usize>();
+
+    /// Returns `None` if `size + HEADER_SIZE + ALIGNMENT - 1` overflows or
+    /// the resulting layout is otherwise invalid.
+    ///
+    /// Enough slack is requested for `HEADER_SIZE` bytes of header plus up
+    /// to `ALIGNMENT - 1` bytes lost to rounding `data_ptr` up.
+    fn layout_for(size: usize) -> Option<Layout> {
+        let total_size = size
+            .checked_add(HEADER_SIZE)?
+            .checked_add(ALIGNMENT - 1)?;
+        Layout::from_size_align(total_size, ALIGNMENT).ok()
+    }
+
+    unsafe fn round_up(addr: usize, align: usize) -> usize {
+        (addr + align - 1) & !(align - 1)
+    }
+
+    pub unsafe extern "C" fn malloc(size: usize) -> *mut c_void {
+        // See the packed variant's `malloc` for why every failure path
+        // here returns `null_mut()` instead of panicking or aborting.
+        let Some(layout) = layout_for(size) else {
+            return std::ptr::null_mut();
+        };
+
+        let base = alloc(layout);
+        if base.is_null() {
+            return std::ptr::null_mut();
+        }
+        let data_ptr = round_up(base as usize + HEADER_SIZE, ALIGNMENT) as *mut u8;
+        *(data_ptr.sub(2 * size_of::<usize>()) as *mut usize) = data_ptr as usize - base as usize;
+        *(data_ptr.sub(size_of::<usize>()) as *mut usize) = size;
+        #[cfg(feature = "stats")]
+        super::stats::record_alloc(size);
+        data_ptr as _
+    }
+
+    pub unsafe extern "C" fn free(data_ptr: *mut c_void) {
+        let data_ptr = data_ptr as *mut u8;
+        let offset = *(data_ptr.sub(2 * size_of::<usize>()) as *const usize);
+        let size = *(data_ptr.sub(size_of::<usize>()) as *const usize);
+        let base = data_ptr.sub(offset);
+        #[cfg(feature = "stats")]
+        super::stats::record_free(size);
+        // Same size that was already used to build a successful allocation
+        // in `malloc` above; recomputing its layout here can't fail short of
+        // header corruption.
+        let layout = layout_for(size).expect("corrupted allocation header: size overflows layout");
+        dealloc(base, layout)
+    }
 }
 
+#[cfg(not(feature = "cache-align"))]
+use packed::{free as free_impl, malloc as malloc_impl};
+#[cfg(feature = "cache-align")]
+use aligned::{free as free_impl, malloc as malloc_impl};
+
 unsafe extern "C" fn malloc(size: usize) -> *mut c_void {
-    let size_and_data_ptr = alloc(layout_for(size));
-    *(size_and_data_ptr as *mut usize) = size;
-    size_and_data_ptr.add(size_of::<usize>()) as _
+    malloc_impl(size)
 }
 
 unsafe extern "C" fn free(data_ptr: *mut c_void) {
-    let size_and_data_ptr = data_ptr.sub(size_of::<usize>());
-    let size = *(size_and_data_ptr as *const usize);
-    dealloc(size_and_data_ptr as _, layout_for(size))
+    free_impl(data_ptr)
 }
 
 pub static OPTIONS: libdeflate_options = libdeflate_options {
     sizeof_options: size_of::<libdeflate_options>(),
     malloc_func: Some(malloc),
     free_func: Some(free),
-};
\ No newline at end of file
+};
+
+/// Builds a [`libdeflate_options`] that routes libdeflate's allocations
+/// through a caller-supplied [`GlobalAlloc`] instead of [`OPTIONS`]'s fixed
+/// use of the process's global allocator -- for embedding libdeflate inside
+/// something that needs to account for or constrain all of its allocations
+/// through a single arena, pool, or other custom backend.
+///
+/// `libdeflate_options::malloc_func`/`free_func` are plain C function
+/// pointers with no user-data slot for libdeflate to thread a context
+/// pointer back through, so there's nowhere to stash a reference to the
+/// chosen allocator inside the options struct itself. Instead, the
+/// allocator is leaked to `'static` and installed in a process-wide slot
+/// that the trampoline functions below read from.
+///
+/// `custom_free` always dispatches through whatever allocator is
+/// *currently* installed, not the one that actually performed the matching
+/// `alloc` -- so a second `LibdeflateAllocator` replacing the slot while a
+/// compressor/decompressor built from the first one is still alive (or
+/// just hasn't freed its buffers yet) would free through the wrong
+/// backend, which is UB for anything but the global allocator. Rather than
+/// risk that, only one `LibdeflateAllocator` is allowed to be outstanding
+/// at a time: `new` panics if one already is, and `Drop` clears the slot
+/// so the next one can be constructed. A compressor/decompressor still
+/// holding a pointer to a dropped instance's `options()` simply stops
+/// freeing (the trampolines see no allocator installed and leak the
+/// buffer) rather than freeing through a since-replaced backend.
+pub struct LibdeflateAllocator {
+    options: libdeflate_options,
+}
+
+impl LibdeflateAllocator {
+    /// Route libdeflate's allocations through `allocator` instead of the
+    /// global Rust allocator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if another `LibdeflateAllocator` is already outstanding --
+    /// drop it first. See the type-level docs for why overlapping
+    /// instances can't be allowed to coexist.
+    #[must_use]
+    pub fn new(allocator: impl GlobalAlloc + Sync + 'static) -> Self {
+        assert!(
+            !ALLOCATOR_IN_USE.swap(true, Ordering::AcqRel),
+            "a LibdeflateAllocator is already active; drop it before constructing another"
+        );
+
+        let leaked: &'static (dyn GlobalAlloc + Sync) = Box::leak(Box::new(allocator));
+        CURRENT_ALLOCATOR.store(Box::leak(Box::new(leaked)), Ordering::Release);
+
+        Self {
+            options: libdeflate_options {
+                sizeof_options: size_of::<libdeflate_options>(),
+                malloc_func: Some(custom_malloc),
+                free_func: Some(custom_free),
+            },
+        }
+    }
+
+    /// The `libdeflate_options` to pass wherever this crate accepts one,
+    /// e.g. a compressor/decompressor's `new_with_options` constructor.
+    #[must_use]
+    pub fn options(&self) -> &libdeflate_options {
+        &self.options
+    }
+}
+
+impl Drop for LibdeflateAllocator {
+    fn drop(&mut self) {
+        CURRENT_ALLOCATOR.store(std::ptr::null_mut(), Ordering::Release);
+        ALLOCATOR_IN_USE.store(false, Ordering::Release);
+    }
+}
+
+static ALLOCATOR_IN_USE: AtomicBool = AtomicBool::new(false);
+
+static CURRENT_ALLOCATOR: AtomicPtr<&'static (dyn GlobalAlloc + Sync)> =
+    AtomicPtr::new(std::ptr::null_mut());
+
+unsafe fn current_allocator() -> Option<&'static (dyn GlobalAlloc + Sync)> {
+    CURRENT_ALLOCATOR.load(Ordering::Acquire).as_ref().copied()
+}
+
+/// Same packed `[size][data]` layout as the default allocator's `packed`
+/// module (see its `layout_for`), but kept independent since `packed` is
+/// gated behind `cache-align` being off while these trampolines exist
+/// unconditionally.
+fn custom_layout_for(size: usize) -> Option<Layout> {
+    let total_size = size_of::<usize>().checked_add(size)?;
+    Layout::from_size_align(total_size, align_of::<usize>()).ok()
+}
+
+unsafe extern "C" fn custom_malloc(size: usize) -> *mut c_void {
+    let Some(allocator) = current_allocator() else {
+        return std::ptr::null_mut();
+    };
+    let Some(layout) = custom_layout_for(size) else {
+        return std::ptr::null_mut();
+    };
+
+    let size_and_data_ptr = allocator.alloc(layout);
+    if size_and_data_ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+    *(size_and_data_ptr as *mut usize) = size;
+    size_and_data_ptr.add(size_of::<usize>()) as _
+}
+
+unsafe extern "C" fn custom_free(data_ptr: *mut c_void) {
+    // `custom_free` is only ever installed as part of a `libdeflate_options`
+    // built from a `LibdeflateAllocator`, so the allocator that allocated
+    // this pointer is still the one installed now (construction replaces
+    // the previous backend, but doesn't free what it already handed out).
+    let Some(allocator) = current_allocator() else {
+        return;
+    };
+    let size_and_data_ptr = (data_ptr as *mut u8).sub(size_of::<usize>());
+    let size = *(size_and_data_ptr as *const usize);
+    // Same size that was already used to build a successful allocation in
+    // `custom_malloc` above; recomputing its layout here can't fail short of
+    // header corruption.
+    let layout =
+        custom_layout_for(size).expect("corrupted allocation header: size overflows layout");
+    allocator.dealloc(size_and_data_ptr, layout)
+}
\ No newline at end of file
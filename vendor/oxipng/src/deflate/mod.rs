@@ -3,6 +3,8 @@ mod deflater;
 use std::num::NonZeroU8;
 use std::{fmt, fmt::Display};
 
+use log::warn;
+
 pub use deflater::{crc32, deflate, inflate};
 
 use crate::{PngError, PngResult};
@@ -11,6 +13,9 @@ mod zopfli_oxipng;
 #[cfg(feature = "zopfli")]
 pub use zopfli_oxipng::deflate as zopfli_deflate;
 
+mod rust_deflate;
+pub use rust_deflate::{HuffmanStrategy, RustDeflateMode, RustDeflateParams};
+
 /// DEFLATE algorithms supported by oxipng (for use in [`Options`][crate::Options])
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Deflaters {
@@ -27,6 +32,15 @@ pub enum Deflaters {
         /// less iterations, or else they will be too slow.
         iterations: NonZeroU8,
     },
+    /// Use a dependency-free pure-Rust encoder.
+    ///
+    /// Slower and slightly less dense than `Libdeflater`, but has no native
+    /// dependencies, so it works on targets such as `wasm32` where linking a
+    /// C library isn't practical.
+    RustDeflate {
+        /// Block size, Huffman strategy and match-finder effort to use
+        params: RustDeflateParams,
+    },
 }
 
 impl Deflaters {
@@ -35,6 +49,7 @@ impl Deflaters {
             Self::Libdeflater { compression } => deflate(data, compression, max_size)?,
             #[cfg(feature = "zopfli")]
             Self::Zopfli { iterations } => zopfli_deflate(data, iterations)?,
+            Self::RustDeflate { params } => rust_deflate::compress(data, params)?,
         };
         if let Some(max) = max_size {
             if compressed.len() > max {
@@ -43,6 +58,25 @@ impl Deflaters {
         }
         Ok(compressed)
     }
+
+    /// Set the DEFLATE block size, in bytes, if this backend supports tuning it.
+    ///
+    /// Only [`RustDeflate`][Self::RustDeflate] currently exposes a block size; other
+    /// backends log a warning and are left unchanged.
+    pub(crate) fn set_block_size(&mut self, block_size: usize) {
+        match self {
+            Self::RustDeflate { params } => params.block_size = block_size,
+            _ => warn!("{self} does not support a configurable block size; ignoring"),
+        }
+    }
+
+    /// Set the Huffman block strategy, if this backend supports tuning it.
+    pub(crate) fn set_huffman_strategy(&mut self, huffman: HuffmanStrategy) {
+        match self {
+            Self::RustDeflate { params } => params.huffman = huffman,
+            _ => warn!("{self} does not support a configurable Huffman strategy; ignoring"),
+        }
+    }
 }
 
 impl Display for Deflaters {
@@ -52,6 +86,7 @@ impl Display for Deflaters {
             Self::Libdeflater { compression } => write!(f, "zc = {compression}"),
             #[cfg(feature = "zopfli")]
             Self::Zopfli { iterations } => write!(f, "zopfli, zi = {iterations}"),
+            Self::RustDeflate { params } => write!(f, "rust-deflate, mode = {:?}", params.mode),
         }
     }
 }
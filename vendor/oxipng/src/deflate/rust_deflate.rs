@@ -0,0 +1,858 @@
+//! A dependency-free, pure-Rust DEFLATE encoder.
+//!
+//! This exists so oxipng can produce valid zlib streams on targets where
+//! linking a C library (libdeflate) isn't an option, most notably
+//! `wasm32-unknown-unknown` without `wasm32-wasi` shims. It trades some
+//! compression ratio and speed for being a plain `.rs` file with no build
+//! script.
+
+use crate::{PngError, PngResult};
+
+/// Speed/ratio tradeoff for [`RustDeflate`][super::Deflaters::RustDeflate].
+///
+/// This controls only the match-finder effort (hash-chain length and lazy
+/// matching); block size and Huffman block type are configured separately
+/// via [`RustDeflateParams`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RustDeflateMode {
+    /// Short hash chains, no lazy matching. Fastest, weakest compression.
+    Fast,
+    /// A moderate chain length with lazy matching. Good general default.
+    Balanced,
+    /// Long hash chains with lazy matching. Slowest, best compression.
+    Best,
+}
+
+impl RustDeflateMode {
+    /// Maximum number of hash-chain links to follow when searching for a match
+    const fn max_chain(self) -> usize {
+        match self {
+            Self::Fast => 16,
+            Self::Balanced => 128,
+            Self::Best => 1024,
+        }
+    }
+
+    /// Whether to check if the next position yields a longer match before emitting
+    const fn lazy_matching(self) -> bool {
+        !matches!(self, Self::Fast)
+    }
+}
+
+/// Which DEFLATE block type(s) [`RustDeflateEncoder`] is allowed to emit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HuffmanStrategy {
+    /// Always use dynamic Huffman blocks
+    Dynamic,
+    /// Always use the pre-defined fixed Huffman codes
+    Fixed,
+    /// Estimate the bit cost of both and pick whichever is smaller per block
+    Auto,
+}
+
+/// Tunable parameters for [`RustDeflate`][super::Deflaters::RustDeflate]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RustDeflateParams {
+    /// Match-finder effort
+    pub mode: RustDeflateMode,
+    /// How many input bytes to encode per DEFLATE block
+    pub block_size: usize,
+    /// Which Huffman block type(s) to consider
+    pub huffman: HuffmanStrategy,
+}
+
+impl RustDeflateParams {
+    #[must_use]
+    pub const fn new(mode: RustDeflateMode) -> Self {
+        Self {
+            mode,
+            block_size: DEFAULT_BLOCK_SIZE,
+            huffman: HuffmanStrategy::Auto,
+        }
+    }
+}
+
+/// Default block size: compress roughly 1 MiB of input per DEFLATE block
+const DEFAULT_BLOCK_SIZE: usize = 1 << 20;
+
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// Number of entries in the rolling hash table; must be a power of two
+const HASH_SIZE: usize = 1 << 15;
+
+#[inline]
+fn hash3(data: &[u8]) -> usize {
+    let v = u32::from(data[0]) | (u32::from(data[1]) << 8) | (u32::from(data[2]) << 16);
+    ((v.wrapping_mul(2654435761)) >> 17) as usize & (HASH_SIZE - 1)
+}
+
+/// A single LZ77 token: either a literal byte or a length/distance back-reference
+enum Token {
+    Literal(u8),
+    Match { len: u16, dist: u16 },
+}
+
+/// Hash-chain match finder over 3-byte prefixes
+struct MatchFinder<'a> {
+    data: &'a [u8],
+    head: Vec<i32>,
+    prev: Vec<i32>,
+    mode: RustDeflateMode,
+}
+
+impl<'a> MatchFinder<'a> {
+    fn new(data: &'a [u8], mode: RustDeflateMode) -> Self {
+        Self {
+            data,
+            head: vec![-1; HASH_SIZE],
+            prev: vec![-1; data.len().max(1)],
+            mode,
+        }
+    }
+
+    fn insert(&mut self, pos: usize) {
+        if pos + MIN_MATCH > self.data.len() {
+            return;
+        }
+        let h = hash3(&self.data[pos..]);
+        self.prev[pos] = self.head[h];
+        self.head[h] = pos as i32;
+    }
+
+    /// Find the longest match starting at `pos`, if any is at least `MIN_MATCH` long
+    fn find_match(&self, pos: usize) -> Option<(usize, usize)> {
+        if pos + MIN_MATCH > self.data.len() {
+            return None;
+        }
+        let h = hash3(&self.data[pos..]);
+        let max_len = (self.data.len() - pos).min(MAX_MATCH);
+        let mut candidate = self.head[h];
+        let mut chain = self.mode.max_chain();
+        let mut best_len = 0;
+        let mut best_dist = 0;
+        while candidate >= 0 && chain > 0 {
+            let cand = candidate as usize;
+            let dist = pos - cand;
+            if dist > 32768 {
+                break;
+            }
+            let len = common_prefix_len(&self.data[cand..], &self.data[pos..], max_len);
+            if len > best_len {
+                best_len = len;
+                best_dist = dist;
+                if len >= max_len {
+                    break;
+                }
+            }
+            candidate = self.prev[cand];
+            chain -= 1;
+        }
+        (best_len >= MIN_MATCH).then_some((best_len, best_dist))
+    }
+}
+
+fn common_prefix_len(a: &[u8], b: &[u8], max: usize) -> usize {
+    a.iter().zip(b.iter()).take(max).take_while(|(x, y)| x == y).count()
+}
+
+/// Split the input into LZ77 tokens using a hash-chain match finder with optional lazy matching
+fn tokenize(data: &[u8], mode: RustDeflateMode) -> Vec<Token> {
+    let mut finder = MatchFinder::new(data, mode);
+    let mut tokens = Vec::with_capacity(data.len() / 2);
+    let mut pos = 0;
+    // A match found while peeking one byte ahead for lazy matching, carried
+    // into the next iteration so that position is never searched twice: by
+    // the time we peek at `pos + 1` it's already inserted into the hash
+    // chain, so a second `find_match` there would match itself at distance 0.
+    let mut pending: Option<(usize, usize)> = None;
+    while pos < data.len() {
+        let this_match = match pending.take() {
+            Some(m) => Some(m),
+            None => {
+                // `find_match` must run before `insert` for the same
+                // position, or the position immediately matches itself.
+                let m = finder.find_match(pos);
+                finder.insert(pos);
+                m
+            }
+        };
+        match this_match {
+            Some((len, dist)) => {
+                if mode.lazy_matching() && pos + 1 < data.len() {
+                    let next_match = finder.find_match(pos + 1);
+                    finder.insert(pos + 1);
+                    if let Some(next) = next_match {
+                        if next.0 > len {
+                            // Defer to the better match starting one byte later
+                            tokens.push(Token::Literal(data[pos]));
+                            pending = Some(next);
+                            pos += 1;
+                            continue;
+                        }
+                    }
+                    tokens.push(Token::Match {
+                        len: len as u16,
+                        dist: dist as u16,
+                    });
+                    // `pos + 1` is already inserted from the lookahead above
+                    for p in pos + 2..pos + len {
+                        finder.insert(p);
+                    }
+                } else {
+                    tokens.push(Token::Match {
+                        len: len as u16,
+                        dist: dist as u16,
+                    });
+                    for p in pos + 1..pos + len {
+                        finder.insert(p);
+                    }
+                }
+                pos += len;
+            }
+            None => {
+                tokens.push(Token::Literal(data[pos]));
+                pos += 1;
+            }
+        }
+    }
+    tokens
+}
+
+/// A streaming DEFLATE encoder that emits zlib-wrapped output.
+///
+/// Call [`compress`][Self::compress] as many times as needed to feed input, then
+/// [`finish`][Self::finish] to flush the final block and retrieve the zlib stream.
+pub struct RustDeflateEncoder {
+    params: RustDeflateParams,
+    pending: Vec<u8>,
+    out: Vec<u8>,
+    adler: (u32, u32),
+}
+
+impl RustDeflateEncoder {
+    #[must_use]
+    pub fn new(params: RustDeflateParams) -> Self {
+        let mut out = Vec::new();
+        // zlib header: CMF/FLG chosen to match the default window size/level
+        // used by the reference zlib implementation for a "default" stream
+        out.extend_from_slice(&[0x78, 0x9C]);
+        let block_size = params.block_size.max(MAX_MATCH);
+        Self {
+            params: RustDeflateParams { block_size, ..params },
+            pending: Vec::new(),
+            out,
+            adler: (1, 0),
+        }
+    }
+
+    /// Feed more input bytes into the encoder, flushing complete blocks as we go
+    pub fn compress(&mut self, input: &[u8]) {
+        update_adler32(&mut self.adler, input);
+        self.pending.extend_from_slice(input);
+        while self.pending.len() >= self.params.block_size {
+            let block: Vec<u8> = self.pending.drain(..self.params.block_size).collect();
+            encode_block(&block, self.params, false, &mut self.out);
+        }
+    }
+
+    /// Flush the final (possibly partial) block and return the completed zlib stream
+    #[must_use]
+    pub fn finish(mut self) -> Vec<u8> {
+        let block = std::mem::take(&mut self.pending);
+        encode_block(&block, self.params, true, &mut self.out);
+        let (s1, s2) = self.adler;
+        self.out.extend_from_slice(&((s2 << 16) | s1).to_be_bytes());
+        self.out
+    }
+}
+
+fn update_adler32(state: &mut (u32, u32), data: &[u8]) {
+    const MOD_ADLER: u32 = 65521;
+    let (mut s1, mut s2) = *state;
+    for &b in data {
+        s1 = (s1 + u32::from(b)) % MOD_ADLER;
+        s2 = (s2 + s1) % MOD_ADLER;
+    }
+    *state = (s1, s2);
+}
+
+/// A minimal bit writer, LSB-first as required by DEFLATE
+struct BitWriter<'a> {
+    out: &'a mut Vec<u8>,
+    bit_buf: u32,
+    bit_count: u32,
+}
+
+impl<'a> BitWriter<'a> {
+    fn new(out: &'a mut Vec<u8>) -> Self {
+        Self {
+            out,
+            bit_buf: 0,
+            bit_count: 0,
+        }
+    }
+
+    fn write_bits(&mut self, value: u32, bits: u32) {
+        debug_assert!(bits <= 32);
+        // Mask first: callers (notably the dynamic-block header) rely on
+        // out-of-range high bits being dropped rather than bleeding into
+        // whatever gets written next.
+        let mask = if bits >= 32 { u32::MAX } else { (1u32 << bits) - 1 };
+        self.bit_buf |= (value & mask) << self.bit_count;
+        self.bit_count += bits;
+        while self.bit_count >= 8 {
+            self.out.push((self.bit_buf & 0xFF) as u8);
+            self.bit_buf >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    fn align_to_byte(&mut self) {
+        if self.bit_count > 0 {
+            self.out.push((self.bit_buf & 0xFF) as u8);
+            self.bit_buf = 0;
+            self.bit_count = 0;
+        }
+    }
+}
+
+/// Encode one DEFLATE block, choosing fixed vs. dynamic Huffman per `params.huffman`
+fn encode_block(data: &[u8], params: RustDeflateParams, is_final: bool, out: &mut Vec<u8>) {
+    let tokens = tokenize(data, params.mode);
+
+    let use_dynamic = match params.huffman {
+        HuffmanStrategy::Dynamic => true,
+        HuffmanStrategy::Fixed => false,
+        HuffmanStrategy::Auto => {
+            let mut lit_freq = [0u32; 286];
+            let mut dist_freq = [0u32; 30];
+            for t in &tokens {
+                match t {
+                    Token::Literal(b) => lit_freq[*b as usize] += 1,
+                    Token::Match { len, dist } => {
+                        lit_freq[length_code(*len).0 as usize] += 1;
+                        dist_freq[distance_code(*dist).0 as usize] += 1;
+                    }
+                }
+            }
+            lit_freq[256] += 1; // end-of-block symbol
+            estimate_dynamic_bits(&lit_freq, &dist_freq, &tokens) < estimate_fixed_bits(&tokens)
+        }
+    };
+
+    let mut writer = BitWriter::new(out);
+    writer.write_bits(is_final as u32, 1);
+    if use_dynamic {
+        let mut lit_freq = [0u32; 286];
+        let mut dist_freq = [0u32; 30];
+        for t in &tokens {
+            match t {
+                Token::Literal(b) => lit_freq[*b as usize] += 1,
+                Token::Match { len, dist } => {
+                    lit_freq[length_code(*len).0 as usize] += 1;
+                    dist_freq[distance_code(*dist).0 as usize] += 1;
+                }
+            }
+        }
+        lit_freq[256] += 1;
+        writer.write_bits(0b10, 2); // dynamic Huffman
+        write_dynamic_block(&mut writer, &lit_freq, &dist_freq, &tokens);
+    } else {
+        writer.write_bits(0b01, 2); // fixed Huffman
+        write_fixed_block(&mut writer, &tokens);
+    }
+    writer.align_to_byte();
+}
+
+fn estimate_fixed_bits(tokens: &[Token]) -> usize {
+    let mut bits = 0;
+    for t in tokens {
+        bits += match t {
+            Token::Literal(b) => fixed_lit_len_bits(u16::from(*b)),
+            Token::Match { len, dist } => {
+                fixed_lit_len_bits(length_code(*len).0) + 5 + distance_code(*dist).2 as usize
+            }
+        };
+    }
+    bits + fixed_lit_len_bits(256)
+}
+
+fn fixed_lit_len_bits(code: u16) -> usize {
+    // Per RFC 1951 3.2.6: literal/length codes use 7, 8 or 9 bits depending on range
+    match code {
+        0..=143 => 8,
+        144..=255 => 9,
+        256..=279 => 7,
+        _ => 8,
+    }
+}
+
+fn estimate_dynamic_bits(lit_freq: &[u32; 286], dist_freq: &[u32; 30], tokens: &[Token]) -> usize {
+    let lit_lens = build_huffman_lengths(lit_freq, 15);
+    let dist_lens = build_huffman_lengths(dist_freq, 15);
+    let mut bits = 0;
+    for t in tokens {
+        bits += match t {
+            Token::Literal(b) => lit_lens[*b as usize] as usize,
+            Token::Match { len, dist } => {
+                let (lcode, _, lextra) = length_code(*len);
+                let (dcode, _, dextra) = distance_code(*dist);
+                lit_lens[lcode as usize] as usize + lextra as usize + dist_lens[dcode as usize] as usize + dextra as usize
+            }
+        };
+    }
+    bits + lit_lens[256] as usize + header_bits_estimate(&lit_lens, &dist_lens)
+}
+
+/// Rough, fixed overhead for the dynamic-block header (code-length tree + counts)
+fn header_bits_estimate(lit_lens: &[u8], dist_lens: &[u8]) -> usize {
+    // A conservative constant estimate: ~3 bits per used code length symbol on
+    // average, plus the fixed-size HLIT/HDIST/HCLEN fields (5 + 5 + 4 bits).
+    let used = lit_lens.iter().chain(dist_lens.iter()).filter(|&&l| l != 0).count();
+    14 + used * 3
+}
+
+fn write_fixed_block(writer: &mut BitWriter<'_>, tokens: &[Token]) {
+    for t in tokens {
+        match t {
+            Token::Literal(b) => write_fixed_lit_len(writer, u16::from(*b)),
+            Token::Match { len, dist } => {
+                let (code, base, extra_bits) = length_code(*len);
+                write_fixed_lit_len(writer, code);
+                if extra_bits > 0 {
+                    writer.write_bits(u32::from(*len) - u32::from(base), u32::from(extra_bits));
+                }
+                let (dcode, dbase, dextra_bits) = distance_code(*dist);
+                writer.write_bits(reverse_bits(u32::from(dcode), 5), 5);
+                if dextra_bits > 0 {
+                    writer.write_bits(u32::from(*dist) - u32::from(dbase), u32::from(dextra_bits));
+                }
+            }
+        }
+    }
+    write_fixed_lit_len(writer, 256);
+}
+
+fn write_fixed_lit_len(writer: &mut BitWriter<'_>, code: u16) {
+    let (value, bits): (u16, u16) = match code {
+        0..=143 => (code + 0x30, 8),
+        144..=255 => (code - 144 + 0x190, 9),
+        256..=279 => (code - 256, 7),
+        280..=287 => (code - 280 + 0xC0, 8),
+        _ => unreachable!(),
+    };
+    writer.write_bits(reverse_bits(u32::from(value), bits), u32::from(bits));
+}
+
+fn reverse_bits(value: u32, bits: u16) -> u32 {
+    let mut v = value;
+    let mut r = 0;
+    for _ in 0..bits {
+        r = (r << 1) | (v & 1);
+        v >>= 1;
+    }
+    r
+}
+
+fn write_dynamic_block(
+    writer: &mut BitWriter<'_>,
+    lit_freq: &[u32; 286],
+    dist_freq: &[u32; 30],
+    tokens: &[Token],
+) {
+    let lit_lens = build_huffman_lengths(lit_freq, 15);
+    let dist_lens = build_huffman_lengths(dist_freq, 15);
+    let lit_codes = canonical_codes(&lit_lens);
+    let dist_codes = canonical_codes(&dist_lens);
+
+    // Trim trailing zero lengths from both alphabets, keeping the minimum
+    // counts the header can express (257 literal/length, 1 distance).
+    let hlit = 286 - lit_lens.iter().rposition(|&l| l != 0).map_or(285, |p| 285 - p);
+    let hdist = 30 - dist_lens.iter().rposition(|&l| l != 0).map_or(29, |p| 29 - p);
+    let hlit = hlit.max(257).min(286);
+    let hdist = hdist.max(1).min(30);
+
+    // RFC 1951 3.2.7: the concatenated lit/dist code lengths are themselves
+    // RLE-compacted (repeat markers 16/17/18) and Huffman-coded using a
+    // 19-symbol "code length" alphabet, whose own lengths are sent as a
+    // sequence of 3-bit fields in this canonical order.
+    const CLC_ORDER: [usize; 19] = [
+        16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+    ];
+    let combined_lens: Vec<u8> = lit_lens[..hlit]
+        .iter()
+        .chain(dist_lens[..hdist].iter())
+        .copied()
+        .collect();
+    let cl_symbols = rle_code_lengths(&combined_lens);
+
+    let mut clc_freq = [0u32; 19];
+    for &(symbol, _, _) in &cl_symbols {
+        clc_freq[symbol as usize] += 1;
+    }
+    let clc_lens = build_huffman_lengths(&clc_freq, 7);
+    let clc_codes = canonical_codes(&clc_lens);
+
+    let hclen_count = CLC_ORDER
+        .iter()
+        .rposition(|&sym| clc_lens[sym] != 0)
+        .map_or(4, |p| p + 1)
+        .max(4);
+
+    writer.write_bits((hlit - 257) as u32, 5);
+    writer.write_bits((hdist - 1) as u32, 5);
+    writer.write_bits((hclen_count - 4) as u32, 4);
+    for &sym in &CLC_ORDER[..hclen_count] {
+        writer.write_bits(u32::from(clc_lens[sym]), 3);
+    }
+    for &(symbol, extra_bits, extra_value) in &cl_symbols {
+        write_code(writer, &clc_codes, symbol as usize);
+        if extra_bits > 0 {
+            writer.write_bits(u32::from(extra_value), u32::from(extra_bits));
+        }
+    }
+
+    for t in tokens {
+        match t {
+            Token::Literal(b) => write_code(writer, &lit_codes, *b as usize),
+            Token::Match { len, dist } => {
+                let (code, base, extra_bits) = length_code(*len);
+                write_code(writer, &lit_codes, code as usize);
+                if extra_bits > 0 {
+                    writer.write_bits(u32::from(*len) - u32::from(base), u32::from(extra_bits));
+                }
+                let (dcode, dbase, dextra_bits) = distance_code(*dist);
+                write_code(writer, &dist_codes, dcode as usize);
+                if dextra_bits > 0 {
+                    writer.write_bits(u32::from(*dist) - u32::from(dbase), u32::from(dextra_bits));
+                }
+            }
+        }
+    }
+    write_code(writer, &lit_codes, 256);
+}
+
+fn write_code(writer: &mut BitWriter<'_>, codes: &[(u16, u8)], symbol: usize) {
+    let (code, len) = codes[symbol];
+    writer.write_bits(reverse_bits(u32::from(code), len as u16), u32::from(len));
+}
+
+/// RLE-compact a sequence of code lengths per RFC 1951 3.2.7, returning
+/// `(symbol, extra_bits, extra_value)` triples ready to Huffman-code with
+/// the 19-symbol code-length alphabet:
+///
+/// - `0..=15`: a literal code length
+/// - `16`: repeat the previous length 3-6 times (2 extra bits, value 0-3)
+/// - `17`: repeat a zero length 3-10 times (3 extra bits, value 0-7)
+/// - `18`: repeat a zero length 11-138 times (7 extra bits, value 0-127)
+fn rle_code_lengths(lens: &[u8]) -> Vec<(u8, u8, u16)> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < lens.len() {
+        let cur = lens[i];
+        let mut run = 1;
+        while i + run < lens.len() && lens[i + run] == cur {
+            run += 1;
+        }
+        if cur == 0 {
+            let mut remaining = run;
+            while remaining > 0 {
+                if remaining < 3 {
+                    out.push((0, 0, 0));
+                    remaining -= 1;
+                } else if remaining <= 10 {
+                    out.push((17, 3, (remaining - 3) as u16));
+                    remaining = 0;
+                } else {
+                    let take = remaining.min(138);
+                    out.push((18, 7, (take - 11) as u16));
+                    remaining -= take;
+                }
+            }
+        } else {
+            out.push((cur, 0, 0));
+            let mut remaining = run - 1;
+            while remaining > 0 {
+                if remaining < 3 {
+                    out.push((cur, 0, 0));
+                    remaining -= 1;
+                } else {
+                    let take = remaining.min(6);
+                    out.push((16, 2, (take - 3) as u16));
+                    remaining -= take;
+                }
+            }
+        }
+        i += run;
+    }
+    out
+}
+
+/// Build RFC 1951-compliant Huffman code lengths for the given symbol frequencies
+fn build_huffman_lengths(freq: &[u32], max_bits: u8) -> Vec<u8> {
+    let n = freq.len();
+    let mut lens = vec![0u8; n];
+    let active: Vec<usize> = (0..n).filter(|&i| freq[i] > 0).collect();
+    if active.len() <= 1 {
+        if let Some(&only) = active.first() {
+            lens[only] = 1;
+        }
+        return lens;
+    }
+
+    // Simple package-merge-free approach: build a standard (unbounded-depth)
+    // Huffman tree, then length-limit it to `max_bits`.
+    #[derive(Clone)]
+    struct Node {
+        freq: u64,
+        left: i32,
+        right: i32,
+    }
+    let mut nodes: Vec<Node> = active
+        .iter()
+        .map(|&i| Node {
+            freq: freq[i] as u64,
+            left: -1,
+            right: -1,
+        })
+        .collect();
+    let mut heap: Vec<usize> = (0..nodes.len()).collect();
+    while heap.len() > 1 {
+        heap.sort_by_key(|&i| std::cmp::Reverse(nodes[i].freq));
+        let b = heap.pop().unwrap();
+        let a = heap.pop().unwrap();
+        let merged = Node {
+            freq: nodes[a].freq + nodes[b].freq,
+            left: a as i32,
+            right: b as i32,
+        };
+        nodes.push(merged);
+        heap.push(nodes.len() - 1);
+    }
+    let root = heap[0];
+    let mut stack = vec![(root, 0u32)];
+    let mut leaf_depths = vec![0u32; active.len()];
+    while let Some((idx, d)) = stack.pop() {
+        if idx < active.len() {
+            leaf_depths[idx] = d.max(1);
+        } else {
+            stack.push((nodes[idx].left as usize, d + 1));
+            stack.push((nodes[idx].right as usize, d + 1));
+        }
+    }
+
+    // A plain Huffman tree can be deeper than `max_bits` allows (e.g. with
+    // 286 symbols and heavily skewed frequencies). Simply clamping the
+    // overlong depths, as a naive approach might, breaks the Kraft
+    // inequality and produces codes `canonical_codes` can't assign without
+    // collisions. Instead, re-balance the *length histogram* first, using
+    // the same overflow-redistribution zlib's `gen_bitlen` uses: each
+    // excess code beyond `max_bits` is folded into the last valid length,
+    // and one shallower code is pushed one level deeper to pay for it.
+    let max_bits = max_bits as usize;
+    let raw_max = leaf_depths.iter().copied().max().unwrap_or(1) as usize;
+    let mut bl_count = vec![0u32; raw_max.max(max_bits) + 1];
+    for &d in &leaf_depths {
+        bl_count[d as usize] += 1;
+    }
+    let mut overflow: i64 = 0;
+    for len in (max_bits + 1..bl_count.len()).rev() {
+        overflow += i64::from(bl_count[len]);
+        bl_count[max_bits] += bl_count[len];
+        bl_count[len] = 0;
+    }
+    bl_count.truncate(max_bits + 1);
+    while overflow > 0 {
+        let mut bits = max_bits - 1;
+        while bits > 0 && bl_count[bits] == 0 {
+            bits -= 1;
+        }
+        if bits == 0 {
+            break; // alphabet small enough relative to max_bits that this can't happen in practice
+        }
+        bl_count[bits] -= 1;
+        bl_count[bits + 1] += 2;
+        bl_count[max_bits] -= 1;
+        overflow -= 2;
+    }
+
+    // Hand the rebalanced length histogram back out to symbols, shortest
+    // codes to the most frequent symbols first.
+    let mut by_freq: Vec<usize> = (0..active.len()).collect();
+    by_freq.sort_by(|&a, &b| nodes[b].freq.cmp(&nodes[a].freq));
+    let mut next = by_freq.into_iter();
+    for (len, &count) in bl_count.iter().enumerate().skip(1) {
+        for _ in 0..count {
+            let Some(leaf) = next.next() else { break };
+            lens[active[leaf]] = len as u8;
+        }
+    }
+    lens
+}
+
+/// Assign canonical Huffman codes from a set of code lengths
+fn canonical_codes(lens: &[u8]) -> Vec<(u16, u8)> {
+    let max_bits = lens.iter().copied().max().unwrap_or(0);
+    let mut bl_count = vec![0u32; max_bits as usize + 1];
+    for &l in lens {
+        if l > 0 {
+            bl_count[l as usize] += 1;
+        }
+    }
+    let mut code = 0u32;
+    let mut next_code = vec![0u32; max_bits as usize + 1];
+    for bits in 1..=max_bits as usize {
+        code = (code + bl_count[bits - 1]) << 1;
+        next_code[bits] = code;
+    }
+    let mut codes = vec![(0u16, 0u8); lens.len()];
+    for (sym, &len) in lens.iter().enumerate() {
+        if len > 0 {
+            codes[sym] = (next_code[len as usize] as u16, len);
+            next_code[len as usize] += 1;
+        }
+    }
+    codes
+}
+
+/// Map a match length to its DEFLATE length code, code base, and number of extra bits
+fn length_code(len: u16) -> (u16, u16, u8) {
+    const TABLE: [(u16, u8); 29] = [
+        (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0), (9, 0), (10, 0),
+        (11, 1), (13, 1), (15, 1), (17, 1),
+        (19, 2), (23, 2), (27, 2), (31, 2),
+        (35, 3), (43, 3), (51, 3), (59, 3),
+        (67, 4), (83, 4), (99, 4), (115, 4),
+        (131, 5), (163, 5), (195, 5), (227, 5),
+        (258, 0),
+    ];
+    for (i, &(base, extra)) in TABLE.iter().enumerate() {
+        let next_base = TABLE.get(i + 1).map_or(259, |&(b, _)| b);
+        if len >= base && len < next_base {
+            return (257 + i as u16, base, extra);
+        }
+    }
+    unreachable!("length out of DEFLATE range: {len}")
+}
+
+/// Map a match distance (1-based) to its DEFLATE distance code, code base, and extra bits
+fn distance_code(dist: u16) -> (u16, u16, u8) {
+    const TABLE: [(u16, u8); 30] = [
+        (1, 0), (2, 0), (3, 0), (4, 0),
+        (5, 1), (7, 1),
+        (9, 2), (13, 2),
+        (17, 3), (25, 3),
+        (33, 4), (49, 4),
+        (65, 5), (97, 5),
+        (129, 6), (193, 6),
+        (257, 7), (385, 7),
+        (513, 8), (769, 8),
+        (1025, 9), (1537, 9),
+        (2049, 10), (3073, 10),
+        (4097, 11), (6145, 11),
+        (8193, 12), (12289, 12),
+        (16385, 13), (24577, 13),
+    ];
+    for (i, &(base, extra)) in TABLE.iter().enumerate() {
+        let next_base = TABLE.get(i + 1).map_or(32769, |&(b, _)| b);
+        if dist >= base && dist < next_base {
+            return (i as u16, base, extra);
+        }
+    }
+    unreachable!("distance out of DEFLATE range: {dist}")
+}
+
+/// Compress `input` in one shot using the given params, producing a complete zlib stream
+pub fn compress(input: &[u8], params: RustDeflateParams) -> PngResult<Vec<u8>> {
+    let mut encoder = RustDeflateEncoder::new(params);
+    encoder.compress(input);
+    let out = encoder.finish();
+    if out.len() < 6 {
+        // Should be unreachable given the zlib header + adler trailer are always written
+        return Err(PngError::new("rust_deflate produced an invalid stream"));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deflate::inflate;
+
+    fn assert_round_trips(data: &[u8], params: RustDeflateParams) {
+        let compressed = compress(data, params).unwrap();
+        let decompressed = inflate(&compressed, data.len()).unwrap();
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn round_trips_fixed_block() {
+        let params = RustDeflateParams {
+            huffman: HuffmanStrategy::Fixed,
+            ..RustDeflateParams::new(RustDeflateMode::Fast)
+        };
+        assert_round_trips(
+            b"the quick brown fox jumps over the lazy dog, the quick brown fox jumps again",
+            params,
+        );
+    }
+
+    #[test]
+    fn round_trips_dynamic_block() {
+        let params = RustDeflateParams {
+            huffman: HuffmanStrategy::Dynamic,
+            ..RustDeflateParams::new(RustDeflateMode::Best)
+        };
+        let data: Vec<u8> = (0..4096u32).map(|i| (i % 251) as u8).collect();
+        assert_round_trips(&data, params);
+    }
+
+    #[test]
+    fn round_trips_dynamic_block_with_long_runs() {
+        // Long runs of one byte push most code lengths to zero, forcing the
+        // dynamic header's 16/17/18 RLE repeat symbols to actually be used.
+        let params = RustDeflateParams {
+            huffman: HuffmanStrategy::Dynamic,
+            ..RustDeflateParams::new(RustDeflateMode::Balanced)
+        };
+        let mut data = vec![b'a'; 5000];
+        data.extend_from_slice(b"then some mixed tail content to populate more symbols 0123456789");
+        assert_round_trips(&data, params);
+    }
+
+    #[test]
+    fn auto_strategy_round_trips() {
+        let params = RustDeflateParams::new(RustDeflateMode::Balanced);
+        assert_round_trips(b"AutoStrategy should pick whichever block type is smaller.", params);
+    }
+
+    #[test]
+    fn rle_code_lengths_preserves_sequence() {
+        // Decode the RLE stream back into lengths and check it matches the
+        // input exactly, independent of which Huffman codes later wrap it.
+        let lens = [0u8; 140]
+            .iter()
+            .chain([3u8; 8].iter())
+            .chain([0u8; 2].iter())
+            .chain([5u8; 1].iter())
+            .copied()
+            .collect::<Vec<_>>();
+        let symbols = rle_code_lengths(&lens);
+        let mut decoded = Vec::new();
+        for (symbol, extra_bits, extra_value) in symbols {
+            match symbol {
+                0..=15 => decoded.push(symbol),
+                16 => {
+                    let repeat = extra_value + 3;
+                    let prev = *decoded.last().unwrap();
+                    decoded.extend(std::iter::repeat(prev).take(repeat as usize));
+                }
+                17 => decoded.extend(std::iter::repeat(0).take(extra_value as usize + 3)),
+                18 => decoded.extend(std::iter::repeat(0).take(extra_value as usize + 11)),
+                _ => unreachable!(),
+            }
+            let _ = extra_bits;
+        }
+        assert_eq!(decoded, lens);
+    }
+}
@@ -86,6 +86,10 @@ pub enum StripChunks {
     Keep(IndexSet<[u8; 4]>),
     /// All non-critical chunks
     All,
+    /// Surgically rewrite `caBX` (JUMBF) chunks to keep only the listed
+    /// content types, dropping the rest of the JUMBF box hierarchy. Other
+    /// chunks are left untouched. See [`JumbfContentType`].
+    Jumbf(IndexSet<JumbfContentType>),
 }
 
 impl StripChunks {
@@ -96,6 +100,9 @@ impl StripChunks {
             Self::Strip(names) => !names.contains(name),
             Self::Safe => DISPLAY_CHUNKS.contains(name),
             Self::All => false,
+            // A caBX chunk is never dropped wholesale under this policy; it's
+            // surgically rewritten instead. See `rewrite_jumbf`.
+            Self::Jumbf(_) => true,
         }
     }
 }
@@ -116,31 +123,91 @@ pub struct RawChunk<'a> {
 impl RawChunk<'_> {
     // Is it a chunk for C2PA/CAI JUMBF metadata
     pub(crate) fn is_c2pa(&self) -> bool {
-        if self.name == *b"caBX" {
-            if let Some((b"jumb", data)) = parse_jumbf_box(self.data) {
-                if let Some((b"jumd", data)) = parse_jumbf_box(data) {
-                    if data.get(..4) == Some(b"c2pa") {
-                        return true;
-                    }
-                }
-            }
-        }
-        false
+        self.name == *b"caBX"
+            && jumbf_boxes(self.data)
+                .any(|b| jumbf_content_type(b) == Some(JumbfContentType::C2pa))
     }
 }
 
-fn parse_jumbf_box(data: &[u8]) -> Option<(&[u8], &[u8])> {
-    if data.len() < 8 {
+/// One JUMBF box at some nesting level: its 4-byte type code, the box's full
+/// bytes (including the 8-byte length+type header), and its content.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct JumbfBox<'a> {
+    pub box_type: [u8; 4],
+    pub raw: &'a [u8],
+    pub content: &'a [u8],
+}
+
+/// Iterate sibling JUMBF boxes at a single nesting level.
+///
+/// Guards against the same length/overflow edge cases as the original
+/// single-box parser: each box's declared length must cover at least its own
+/// 8-byte header and must not run past the end of `data`.
+pub(crate) fn jumbf_boxes(mut data: &[u8]) -> impl Iterator<Item = JumbfBox<'_>> {
+    std::iter::from_fn(move || {
+        if data.len() < 8 {
+            return None;
+        }
+        let len = read_be_u32(data.get(0..4)?) as usize;
+        if len < 8 || len > data.len() {
+            return None;
+        }
+        let box_type: [u8; 4] = data.get(4..8)?.try_into().unwrap();
+        let content = data.get(8..len)?;
+        let (raw, rest) = data.split_at(len);
+        data = rest;
+        Some(JumbfBox {
+            box_type,
+            raw,
+            content,
+        })
+    })
+}
+
+/// A JUMBF content type recognized for metadata-preservation purposes.
+///
+/// Content-type identification here is a simplification of the real JUMBF
+/// `jumd` descriptor box (which identifies content by a 16-byte UUID, not a
+/// 4-byte ASCII tag) -- the same simplification [`RawChunk::is_c2pa`] already
+/// relied on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JumbfContentType {
+    /// A C2PA content credentials manifest
+    C2pa,
+    /// An embedded thumbnail box
+    Thumbnail,
+    /// Any other/unrecognized JUMBF content type
+    Other,
+}
+
+/// Classify a top-level `jumb` superbox (one sibling box found directly
+/// inside a `caBX` chunk) by its `jumd` descriptor box.
+fn jumbf_content_type(superbox: JumbfBox<'_>) -> Option<JumbfContentType> {
+    if superbox.box_type != *b"jumb" {
         return None;
     }
-    let (len, rest) = data.split_at(4);
-    let len = read_be_u32(len) as usize;
-    if len < 8 || len > data.len() {
-        return None;
+    let jumd = jumbf_boxes(superbox.content).find(|b| b.box_type == *b"jumd")?;
+    Some(match jumd.content.get(..4)? {
+        b"c2pa" => JumbfContentType::C2pa,
+        b"thmb" => JumbfContentType::Thumbnail,
+        _ => JumbfContentType::Other,
+    })
+}
+
+/// Rewrite a `caBX` chunk's JUMBF payload to keep only sibling superboxes
+/// whose content type is in `keep`, surgically dropping the rest instead of
+/// keeping-or-dropping the whole chunk.
+///
+/// Returns `None` if no superbox survives, meaning the chunk should be
+/// dropped entirely.
+pub(crate) fn rewrite_jumbf(data: &[u8], keep: &IndexSet<JumbfContentType>) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    for superbox in jumbf_boxes(data) {
+        if jumbf_content_type(superbox).is_some_and(|ty| keep.contains(&ty)) {
+            out.extend_from_slice(superbox.raw);
+        }
     }
-    let (box_name, data) = rest.split_at(4);
-    let data = data.get(..len - 8)?;
-    Some((box_name, data))
+    (!out.is_empty()).then_some(out)
 }
 
 pub fn parse_next_chunk<'a>(
@@ -288,6 +355,123 @@ pub fn make_iccp(icc: &[u8], deflater: Deflaters, max_size: Option<usize>) -> Pn
     })
 }
 
+/// A parsed `XYZ ` tagged element: CIE tristimulus values as s15Fixed16
+struct IccXyz {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+fn read_s15fixed16(bytes: &[u8]) -> f64 {
+    i32::from_be_bytes(bytes.try_into().unwrap()) as f64 / 65536.0
+}
+
+/// Read the whole tag table: each 12-byte entry as (signature, offset, size)
+fn icc_tag_table(icc: &[u8]) -> Option<Vec<([u8; 4], usize, usize)>> {
+    let tag_count = read_be_u32(icc.get(128..132)?) as usize;
+    let mut tags = Vec::with_capacity(tag_count);
+    for i in 0..tag_count {
+        let entry = icc.get(132 + i * 12..132 + i * 12 + 12)?;
+        let sig: [u8; 4] = entry[0..4].try_into().unwrap();
+        let offset = read_be_u32(&entry[4..8]) as usize;
+        let size = read_be_u32(&entry[8..12]) as usize;
+        tags.push((sig, offset, size));
+    }
+    Some(tags)
+}
+
+fn find_icc_tag<'a>(tags: &[([u8; 4], usize, usize)], icc: &'a [u8], sig: &[u8; 4]) -> Option<&'a [u8]> {
+    let &(_, offset, size) = tags.iter().find(|(s, ..)| s == sig)?;
+    icc.get(offset..offset + size)
+}
+
+/// Parse an `XYZ ` type element (8-byte type header, then 3 s15Fixed16 values)
+fn parse_xyz_tag(data: &[u8]) -> Option<IccXyz> {
+    if data.get(0..4)? != b"XYZ " {
+        return None;
+    }
+    let values = data.get(8..20)?;
+    Some(IccXyz {
+        x: read_s15fixed16(&values[0..4]),
+        y: read_s15fixed16(&values[4..8]),
+        z: read_s15fixed16(&values[8..12]),
+    })
+}
+
+/// Convert a tristimulus value to chromaticity coordinates, scaled by 100000 for `cHRM`
+fn xyz_to_chrm_point(xyz: &IccXyz) -> Option<(u32, u32)> {
+    let sum = xyz.x + xyz.y + xyz.z;
+    if sum <= 0.0 {
+        return None;
+    }
+    let x = (xyz.x / sum * 100_000.0).round();
+    let y = (xyz.y / sum * 100_000.0).round();
+    if !(0.0..=100_000.0).contains(&x) || !(0.0..=100_000.0).contains(&y) {
+        return None;
+    }
+    Some((x as u32, y as u32))
+}
+
+/// Parse a `curv` type element with a single entry, returning the gamma value (x256) if so
+fn parse_single_curve_gamma(data: &[u8]) -> Option<u16> {
+    if data.get(0..4)? != b"curv" {
+        return None;
+    }
+    let count = read_be_u32(data.get(8..12)?);
+    if count != 1 {
+        return None; // Parametric/LUT curves aren't handled
+    }
+    Some(read_be_u16(data.get(12..14)?))
+}
+
+/// Attempt to synthesize a `cHRM`+`gAMA` pair from a simple matrix/TRC ICC profile.
+///
+/// Returns `None` (keeping the `iCCP` chunk) for parametric curves, LUT-based
+/// profiles, non-RGB color spaces, or mismatched per-channel TRCs.
+pub fn icc_to_chrm_gama(icc: &[u8]) -> Option<(Chunk, Chunk)> {
+    let tags = icc_tag_table(icc)?;
+
+    let wtpt = parse_xyz_tag(find_icc_tag(&tags, icc, b"wtpt")?)?;
+    let rxyz = parse_xyz_tag(find_icc_tag(&tags, icc, b"rXYZ")?)?;
+    let gxyz = parse_xyz_tag(find_icc_tag(&tags, icc, b"gXYZ")?)?;
+    let bxyz = parse_xyz_tag(find_icc_tag(&tags, icc, b"bXYZ")?)?;
+
+    let (wx, wy) = xyz_to_chrm_point(&wtpt)?;
+    let (rx, ry) = xyz_to_chrm_point(&rxyz)?;
+    let (gx, gy) = xyz_to_chrm_point(&gxyz)?;
+    let (bx, by) = xyz_to_chrm_point(&bxyz)?;
+
+    let r_gamma = parse_single_curve_gamma(find_icc_tag(&tags, icc, b"rTRC")?)?;
+    let g_gamma = parse_single_curve_gamma(find_icc_tag(&tags, icc, b"gTRC")?)?;
+    let b_gamma = parse_single_curve_gamma(find_icc_tag(&tags, icc, b"bTRC")?)?;
+    if r_gamma != g_gamma || g_gamma != b_gamma {
+        return None; // Per-channel TRCs differ; can't express as a single gAMA
+    }
+
+    let mut chrm_data = Vec::with_capacity(32);
+    for v in [wx, wy, rx, ry, gx, gy, bx, by] {
+        chrm_data.extend_from_slice(&v.to_be_bytes());
+    }
+    if r_gamma == 0 {
+        return None;
+    }
+    // `r_gamma` is a u8Fixed8Number display-gamma exponent (value * 256);
+    // `gAMA` stores the inverse, 100000 / display_gamma, i.e. 25_600_000 /
+    // r_gamma once the /256 is folded in.
+    let gama_value = 25_600_000_u32 / u32::from(r_gamma);
+
+    Some((
+        Chunk {
+            name: *b"cHRM",
+            data: chrm_data,
+        },
+        Chunk {
+            name: *b"gAMA",
+            data: gama_value.to_be_bytes().to_vec(),
+        },
+    ))
+}
+
 /// If the profile is sRGB, extracts the rendering intent value from it
 pub fn srgb_rendering_intent(icc_data: &[u8]) -> Option<u8> {
     let rendering_intent = *icc_data.get(67)?;
@@ -315,8 +499,193 @@ pub fn srgb_rendering_intent(icc_data: &[u8]) -> Option<u8> {
     }
 }
 
-/// Process aux chunks and potentially adjust options before optimizing
-pub fn preprocess_chunks(aux_chunks: &mut Vec<Chunk>, opts: &mut Options) {
+/// A decoded `tEXt`/`zTXt`/`iTXt` chunk: keyword, optional iTXt-only fields,
+/// and the (already decompressed) text payload.
+struct TextChunk {
+    keyword: Vec<u8>,
+    lang_tag: Option<Vec<u8>>,
+    translated_keyword: Option<Vec<u8>>,
+    text: Vec<u8>,
+    is_itxt: bool,
+}
+
+fn split_at_nul(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let pos = data.iter().position(|&b| b == 0)?;
+    Some((&data[..pos], &data[pos + 1..]))
+}
+
+/// Decode a `tEXt`, `zTXt`, or `iTXt` chunk into its keyword and text
+fn parse_text_chunk(chunk: &Chunk) -> Option<TextChunk> {
+    match &chunk.name {
+        b"tEXt" => {
+            let (keyword, text) = split_at_nul(&chunk.data)?;
+            Some(TextChunk {
+                keyword: keyword.to_vec(),
+                lang_tag: None,
+                translated_keyword: None,
+                text: text.to_vec(),
+                is_itxt: false,
+            })
+        }
+        b"zTXt" => {
+            let (keyword, rest) = split_at_nul(&chunk.data)?;
+            let (&compression_method, compressed) = rest.split_first()?;
+            if compression_method != 0 {
+                return None;
+            }
+            let max_size = compressed.len() * 4 + 1000;
+            Some(TextChunk {
+                keyword: keyword.to_vec(),
+                lang_tag: None,
+                translated_keyword: None,
+                text: inflate(compressed, max_size).ok()?,
+                is_itxt: false,
+            })
+        }
+        b"iTXt" => {
+            let (keyword, rest) = split_at_nul(&chunk.data)?;
+            let (&compression_flag, rest) = rest.split_first()?;
+            let (&compression_method, rest) = rest.split_first()?;
+            let (lang_tag, rest) = split_at_nul(rest)?;
+            let (translated_keyword, rest) = split_at_nul(rest)?;
+            let text = match compression_flag {
+                0 => rest.to_vec(),
+                1 if compression_method == 0 => {
+                    let max_size = rest.len() * 4 + 1000;
+                    inflate(rest, max_size).ok()?
+                }
+                _ => return None,
+            };
+            Some(TextChunk {
+                keyword: keyword.to_vec(),
+                lang_tag: Some(lang_tag.to_vec()),
+                translated_keyword: Some(translated_keyword.to_vec()),
+                text,
+                is_itxt: true,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Minimum decompressed payload size, in bytes, at which zlib-compressing a
+/// text chunk is likely to save space over the ~11 bytes of zlib/deflate
+/// framing overhead.
+const TEXT_COMPRESS_THRESHOLD: usize = 32;
+
+/// Build a `tEXt`/`zTXt`/`iTXt` chunk from its decoded parts, writing `text`
+/// either as-is or already-compressed according to `compressed`.
+fn encode_text_chunk(text_chunk: &TextChunk, compressed: bool, text: &[u8]) -> Chunk {
+    if text_chunk.is_itxt {
+        let lang_tag = text_chunk.lang_tag.as_deref().unwrap_or(&[]);
+        let translated_keyword = text_chunk.translated_keyword.as_deref().unwrap_or(&[]);
+        let mut data = Vec::with_capacity(
+            text_chunk.keyword.len() + lang_tag.len() + translated_keyword.len() + text.len() + 5,
+        );
+        data.extend(&text_chunk.keyword);
+        data.push(0);
+        data.push(compressed as u8);
+        data.push(0); // Compression method; zlib is the only one defined
+        data.extend(lang_tag);
+        data.push(0);
+        data.extend(translated_keyword);
+        data.push(0);
+        data.extend(text);
+        Chunk {
+            name: *b"iTXt",
+            data,
+        }
+    } else if compressed {
+        let mut data = Vec::with_capacity(text_chunk.keyword.len() + text.len() + 2);
+        data.extend(&text_chunk.keyword);
+        data.extend([0, 0]); // Null separator, zlib compression method
+        data.extend(text);
+        Chunk {
+            name: *b"zTXt",
+            data,
+        }
+    } else {
+        let mut data = Vec::with_capacity(text_chunk.keyword.len() + text.len() + 1);
+        data.extend(&text_chunk.keyword);
+        data.push(0);
+        data.extend(text);
+        Chunk {
+            name: *b"tEXt",
+            data,
+        }
+    }
+}
+
+/// Re-encode a decoded text chunk, keeping whichever of the
+/// compressed/uncompressed representations is smaller
+fn make_text_chunk(text_chunk: &TextChunk, deflater: Deflaters) -> Chunk {
+    let best = encode_text_chunk(text_chunk, false, &text_chunk.text);
+    if text_chunk.text.len() < TEXT_COMPRESS_THRESHOLD {
+        return best;
+    }
+    match deflater.deflate(&text_chunk.text, Some(best.data.len())) {
+        Ok(compressed_text) => {
+            let candidate = encode_text_chunk(text_chunk, true, &compressed_text);
+            if candidate.data.len() < best.data.len() {
+                candidate
+            } else {
+                best
+            }
+        }
+        Err(_) => best,
+    }
+}
+
+/// Parse, recompress, and de-duplicate `tEXt`/`zTXt`/`iTXt` chunks.
+///
+/// Each chunk is re-encoded with whichever of the compressed/uncompressed
+/// forms (and, for non-international text, `tEXt`/`zTXt`) is smaller, and
+/// exact keyword+text duplicates are dropped. Chunks that `opts.strip` would
+/// remove anyway are left untouched, since recompressing them is wasted work.
+fn recompress_text_chunks(aux_chunks: &mut Vec<Chunk>, opts: &Options) {
+    if !opts.idat_recoding {
+        return;
+    }
+
+    let mut seen = IndexSet::new();
+    let mut i = 0;
+    while i < aux_chunks.len() {
+        let name = aux_chunks[i].name;
+        if !matches!(&name, b"tEXt" | b"zTXt" | b"iTXt") || !opts.strip.keep(&name) {
+            i += 1;
+            continue;
+        }
+
+        let Some(text_chunk) = parse_text_chunk(&aux_chunks[i]) else {
+            i += 1;
+            continue;
+        };
+
+        if !seen.insert((text_chunk.keyword.clone(), text_chunk.text.clone())) {
+            trace!(
+                "Removing duplicate {} chunk",
+                String::from_utf8_lossy(&text_chunk.keyword)
+            );
+            aux_chunks.remove(i);
+            continue;
+        }
+
+        aux_chunks[i] = make_text_chunk(&text_chunk, opts.deflate);
+        i += 1;
+    }
+}
+
+/// Process aux chunks and potentially adjust options before optimizing.
+///
+/// Returns the original `sBIT` chunk's per-channel significant-bit counts, if
+/// present, so [`postprocess_chunks`] can rewrite rather than discard it if
+/// the bit depth or color type ends up changing.
+pub fn preprocess_chunks(aux_chunks: &mut Vec<Chunk>, opts: &mut Options) -> Option<Vec<u8>> {
+    let orig_sbit = aux_chunks
+        .iter()
+        .find(|c| &c.name == b"sBIT")
+        .map(|c| c.data.clone());
+
     let has_srgb = aux_chunks.iter().any(|c| &c.name == b"sRGB");
     // Grayscale conversion should not be performed if the image is not in the sRGB colorspace
     // An sRGB profile would need to be stripped on conversion, so disallow if stripping is disabled
@@ -345,6 +714,13 @@ pub fn preprocess_chunks(aux_chunks: &mut Vec<Chunk>, opts: &mut Options) {
                     data: vec![intent],
                 };
                 allow_grayscale = true;
+            } else if let Some((chrm, gama)) = (opts.icc_to_chrm_gama && may_replace_iccp)
+                .then(|| icc_to_chrm_gama(&icc))
+                .flatten()
+            {
+                trace!("Replacing iCCP chunk with equivalent cHRM+gAMA chunks");
+                aux_chunks[iccp_idx] = chrm;
+                aux_chunks.insert(iccp_idx + 1, gama);
             } else if opts.idat_recoding {
                 // Try recompressing the profile
                 let cur_len = aux_chunks[iccp_idx].data.len();
@@ -360,30 +736,117 @@ pub fn preprocess_chunks(aux_chunks: &mut Vec<Chunk>, opts: &mut Options) {
         }
     }
 
+    recompress_text_chunks(aux_chunks, opts);
+
     if !allow_grayscale && opts.grayscale_reduction {
         debug!("Disabling grayscale reduction due to presence of sRGB or iCCP chunk");
         opts.grayscale_reduction = false;
     }
 
     // Check for APNG by presence of acTL chunk
-    if aux_chunks.iter().any(|c| &c.name == b"acTL") {
-        warn!("APNG detected, disabling all reductions");
+    if let Some(actl) = aux_chunks.iter().find(|c| &c.name == b"acTL") {
+        let num_frames = actl.data.get(0..4).map(read_be_u32).unwrap_or_default();
+        let num_plays = actl.data.get(4..8).map(read_be_u32).unwrap_or_default();
+        debug!(
+            "APNG detected ({num_frames} frames, {} plays); disabling reductions that would \
+             desync the default image from its frame data",
+            if num_plays == 0 {
+                "infinite".to_owned()
+            } else {
+                num_plays.to_string()
+            }
+        );
+        // Every frame's raw data shares the default image's bit depth and
+        // color type, and assumes non-interlaced scanline order; changing any
+        // of those for the default image alone would desync the frames from
+        // it. Frame payloads are still recompressed independently -- see
+        // `PngData::recompress_frames`.
         opts.interlace = None;
         opts.bit_depth_reduction = false;
         opts.color_type_reduction = false;
         opts.palette_reduction = false;
         opts.grayscale_reduction = false;
     }
+
+    orig_sbit
+}
+
+/// Number of `sBIT` values for a color type, per the PNG spec's sBIT table
+fn sbit_channel_count(color_type: &ColorType) -> usize {
+    match color_type {
+        ColorType::Grayscale { .. } => 1,
+        ColorType::GrayscaleAlpha => 2,
+        ColorType::RGB { .. } | ColorType::Indexed { .. } => 3,
+        ColorType::RGBA => 4,
+    }
+}
+
+/// Rewrite an `sBIT` chunk's per-channel significant-bit counts for a new
+/// bit depth/color type, collapsing or selecting channels as needed.
+///
+/// Returns `None` if `orig_sbit` doesn't match `orig_color_type`'s channel
+/// count (malformed chunk), or if the color type change is not one of the
+/// pairs matched below; `GrayscaleAlpha` to `Grayscale`, for example, is not
+/// handled, and `postprocess_chunks` then drops the `sBIT` chunk.
+fn rewrite_sbit(
+    orig_sbit: &[u8],
+    orig_color_type: &ColorType,
+    new_color_type: &ColorType,
+    new_bit_depth: BitDepth,
+) -> Option<Vec<u8>> {
+    if orig_sbit.len() != sbit_channel_count(orig_color_type) {
+        return None;
+    }
+
+    // Collapse/select the channels that survive the color type change
+    let collapsed: Vec<u8> = if orig_color_type == new_color_type {
+        orig_sbit.to_vec()
+    } else {
+        match (orig_color_type, new_color_type) {
+            (ColorType::RGB { .. } | ColorType::Indexed { .. }, ColorType::Grayscale { .. }) => {
+                vec![orig_sbit[0]]
+            }
+            (ColorType::RGBA, ColorType::GrayscaleAlpha) => {
+                vec![orig_sbit[0], orig_sbit[3]]
+            }
+            (ColorType::RGBA, ColorType::RGB { .. } | ColorType::Indexed { .. }) => {
+                orig_sbit[..3].to_vec()
+            }
+            (ColorType::RGB { .. }, ColorType::Indexed { .. })
+            | (ColorType::Indexed { .. }, ColorType::RGB { .. }) => orig_sbit.to_vec(),
+            _ => return None,
+        }
+    };
+
+    // A reduced bit depth can't claim more significant bits than it has --
+    // except for Indexed, where sBIT describes the (always 8-bit) palette
+    // entries' RGB precision, not the index storage depth, so reducing the
+    // index storage to e.g. 4 bits must not clamp these down too.
+    let clamp = match new_color_type {
+        ColorType::Indexed { .. } => 8,
+        _ => new_bit_depth as u8,
+    };
+    Some(collapsed.into_iter().map(|v| v.min(clamp)).collect())
 }
 
 /// Perform cleanup of certain aux chunks after optimization has been completed
-pub fn postprocess_chunks(aux_chunks: &mut Vec<Chunk>, ihdr: &IhdrData, orig_ihdr: &IhdrData) {
+pub fn postprocess_chunks(
+    aux_chunks: &mut Vec<Chunk>,
+    ihdr: &IhdrData,
+    orig_ihdr: &IhdrData,
+    orig_sbit: Option<&[u8]>,
+) {
     // If the depth/color type has changed, some chunks may be invalid and should be dropped
     // While these could potentially be converted, they have no known use case today and are
     // generally more trouble than they're worth
     if orig_ihdr.bit_depth != ihdr.bit_depth || orig_ihdr.color_type != ihdr.color_type {
+        let new_sbit = orig_sbit
+            .and_then(|sbit| rewrite_sbit(sbit, &orig_ihdr.color_type, &ihdr.color_type, ihdr.bit_depth));
+
         aux_chunks.retain(|c| {
-            let invalid = &c.name == b"bKGD" || &c.name == b"sBIT" || &c.name == b"hIST";
+            let invalid = &c.name == b"bKGD"
+                || &c.name == b"hIST"
+                || (&c.name == b"sBIT" && new_sbit.is_none());
             if invalid {
                 warn!(
                     "Removing {} chunk as it no longer matches the image data",
@@ -392,6 +855,13 @@ pub fn postprocess_chunks(aux_chunks: &mut Vec<Chunk>, ihdr: &IhdrData, orig_ihd
             }
             !invalid
         });
+
+        if let Some(new_sbit) = new_sbit {
+            if let Some(c) = aux_chunks.iter_mut().find(|c| &c.name == b"sBIT") {
+                debug!("Rewrote sBIT chunk for new bit depth/color type");
+                c.data = new_sbit;
+            }
+        }
     }
 
     // Remove any sRGB or iCCP chunks if the image was converted to or from grayscale
@@ -408,3 +878,111 @@ pub fn postprocess_chunks(aux_chunks: &mut Vec<Chunk>, ihdr: &IhdrData, orig_ihd
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s15fixed16(v: f64) -> [u8; 4] {
+        ((v * 65536.0).round() as i32).to_be_bytes()
+    }
+
+    fn xyz_tag_bytes(x: f64, y: f64, z: f64) -> Vec<u8> {
+        let mut v = b"XYZ \0\0\0\0".to_vec();
+        v.extend_from_slice(&s15fixed16(x));
+        v.extend_from_slice(&s15fixed16(y));
+        v.extend_from_slice(&s15fixed16(z));
+        v
+    }
+
+    fn curv_tag_bytes(gamma_x256: u16) -> Vec<u8> {
+        let mut v = b"curv\0\0\0\0".to_vec();
+        v.extend_from_slice(&1u32.to_be_bytes());
+        v.extend_from_slice(&gamma_x256.to_be_bytes());
+        v
+    }
+
+    /// Assemble a minimal ICC profile: a 128-byte header, a tag table, then
+    /// the tagged data blocks themselves, laid out exactly as
+    /// `icc_tag_table`/`find_icc_tag` expect.
+    fn build_profile(tags: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let table_size = tags.len() * 12;
+        let mut offset = 128 + 4 + table_size;
+        let mut entries = Vec::with_capacity(tags.len());
+        let mut data_section = Vec::new();
+        for (sig, bytes) in tags {
+            entries.push((**sig, offset, bytes.len()));
+            data_section.extend_from_slice(bytes);
+            offset += bytes.len();
+        }
+        let mut icc = vec![0u8; 128];
+        icc.extend_from_slice(&(tags.len() as u32).to_be_bytes());
+        for (sig, off, size) in entries {
+            icc.extend_from_slice(&sig);
+            icc.extend_from_slice(&(off as u32).to_be_bytes());
+            icc.extend_from_slice(&(size as u32).to_be_bytes());
+        }
+        icc.extend_from_slice(&data_section);
+        icc
+    }
+
+    #[test]
+    fn icc_to_chrm_gama_inverts_display_gamma() {
+        // 563 / 256 == 2.19921875, a typical u8Fixed8Number encoding of a
+        // "gamma 2.2" single-curve TRC.
+        let curve = curv_tag_bytes(563);
+        let icc = build_profile(&[
+            (b"wtpt", xyz_tag_bytes(0.9505, 1.0, 1.089)),
+            (b"rXYZ", xyz_tag_bytes(0.4, 0.2, 0.02)),
+            (b"gXYZ", xyz_tag_bytes(0.3, 0.6, 0.1)),
+            (b"bXYZ", xyz_tag_bytes(0.15, 0.2, 0.9)),
+            (b"rTRC", curve.clone()),
+            (b"gTRC", curve.clone()),
+            (b"bTRC", curve),
+        ]);
+
+        let (chrm, gama) = icc_to_chrm_gama(&icc).expect("minimal profile should parse");
+        assert_eq!(chrm.name, *b"cHRM");
+        assert_eq!(gama.name, *b"gAMA");
+        // gAMA stores 100000 / display_gamma, not display_gamma * 100000.
+        let expected = 25_600_000_u32 / 563;
+        assert_eq!(u32::from_be_bytes(gama.data.try_into().unwrap()), expected);
+    }
+
+    #[test]
+    fn icc_to_chrm_gama_rejects_zero_gamma() {
+        let curve = curv_tag_bytes(0);
+        let icc = build_profile(&[
+            (b"wtpt", xyz_tag_bytes(0.9505, 1.0, 1.089)),
+            (b"rXYZ", xyz_tag_bytes(0.4, 0.2, 0.02)),
+            (b"gXYZ", xyz_tag_bytes(0.3, 0.6, 0.1)),
+            (b"bXYZ", xyz_tag_bytes(0.15, 0.2, 0.9)),
+            (b"rTRC", curve.clone()),
+            (b"gTRC", curve.clone()),
+            (b"bTRC", curve),
+        ]);
+        assert!(icc_to_chrm_gama(&icc).is_none());
+    }
+
+    #[test]
+    fn rewrite_sbit_indexed_not_clamped_to_index_storage_depth() {
+        let orig = ColorType::RGB {
+            transparent_color: None,
+        };
+        let new = ColorType::Indexed { palette: vec![] };
+        // sBIT for an indexed image describes the (always 8-bit) palette
+        // entries' RGB precision, not the index storage depth, so shrinking
+        // the index storage to 4 bits must not drag these down too.
+        let out = rewrite_sbit(&[8, 8, 8], &orig, &new, BitDepth::Four).unwrap();
+        assert_eq!(out, vec![8, 8, 8]);
+    }
+
+    #[test]
+    fn rewrite_sbit_non_indexed_still_clamped_to_new_bit_depth() {
+        let orig = ColorType::Grayscale {
+            transparent_shade: None,
+        };
+        let out = rewrite_sbit(&[8], &orig, &orig, BitDepth::Four).unwrap();
+        assert_eq!(out, vec![4]);
+    }
+}
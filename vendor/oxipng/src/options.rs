@@ -7,7 +7,13 @@ use std::{
 use indexmap::{indexset, IndexSet};
 use log::warn;
 
-use crate::{deflate::Deflaters, filters::RowFilter, headers::StripChunks, interlace::Interlacing};
+use crate::{
+    deflate::{Deflaters, HuffmanStrategy},
+    filters::RowFilter,
+    headers::StripChunks,
+    interlace::Interlacing,
+    reduction::alpha::AlphaOptim,
+};
 
 /// Write destination for [`optimize`][crate::optimize].
 /// You can use [`optimize_from_memory`](crate::optimize_from_memory) to avoid external I/O.
@@ -50,6 +56,37 @@ impl OutFile {
     }
 }
 
+/// Resource limits enforced while decoding a PNG
+///
+/// These guard against a small, possibly malicious file forcing huge
+/// allocations, e.g. one that advertises an enormous width/height in its
+/// `IHDR`, or whose `IDAT` is a decompression bomb that expands far beyond
+/// its compressed size. Limits are checked before the corresponding
+/// allocation/decompression is attempted, not after.
+///
+/// The defaults are generous enough that legitimate images are never
+/// affected; tighten them when optimizing files from an untrusted source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum number of pixels (`width * height`) a decoded image may have
+    ///
+    /// Default: `2^26` (e.g. a 16384x4096 image, ~67 million pixels)
+    pub max_pixels: u64,
+    /// Maximum size, in bytes, that a chunk may decompress to
+    ///
+    /// Default: `2^30` (1 GiB)
+    pub max_decompressed_bytes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_pixels: 1 << 26,
+            max_decompressed_bytes: 1 << 30,
+        }
+    }
+}
+
 /// Where to read images from in [`optimize`][crate::optimize].
 /// You can use [`optimize_from_memory`](crate::optimize_from_memory) to avoid external I/O.
 #[derive(Clone, Debug)]
@@ -107,10 +144,15 @@ pub struct Options {
     ///
     /// Default: `Some(Interlacing::None)`
     pub interlace: Option<Interlacing>,
-    /// Whether to allow transparent pixels to be altered to improve compression.
+    /// Which alpha optimization strategies to try on fully-transparent pixels.
     ///
-    /// Default: `false`
-    pub optimize_alpha: bool,
+    /// Each strategy in the set is applied to a scratch copy of the image and
+    /// run through the normal filter/deflate fast-evaluation; the smallest
+    /// result wins, the same way individual [`RowFilter`]s compete. Use
+    /// [`AlphaOptim::set_from_bool`] to get the old boolean behavior.
+    ///
+    /// Default: `{NoOp}`
+    pub alpha: IndexSet<AlphaOptim>,
     /// Whether to attempt bit depth reduction
     ///
     /// Default: `true`
@@ -156,6 +198,18 @@ pub struct Options {
     ///
     /// Default: `None`
     pub timeout: Option<Duration>,
+    /// Whether to replace a color-managed `iCCP` chunk with the much smaller
+    /// `cHRM`+`gAMA` pair when the embedded profile is a simple matrix/TRC
+    /// profile that can be losslessly expressed that way.
+    ///
+    /// Only takes effect when `strip` permits dropping `iCCP`.
+    ///
+    /// Default: `false`
+    pub icc_to_chrm_gama: bool,
+    /// Resource limits enforced while decoding the input file
+    ///
+    /// Default: [`Limits::default`]
+    pub limits: Limits,
 }
 
 impl Options {
@@ -189,6 +243,14 @@ impl Options {
         if let Deflaters::Libdeflater { compression } = &mut self.deflate {
             *compression = 5;
         }
+        // Favor speed: small blocks, no per-block fixed/dynamic comparison.
+        // Only RustDeflate has these knobs; every other backend would just
+        // warn! and no-op, and Libdeflater (the default) is by far the
+        // common case, so don't spam that warning on every preset-0 run.
+        if matches!(self.deflate, Deflaters::RustDeflate { .. }) {
+            self.deflate.set_block_size(1 << 15);
+            self.deflate.set_huffman_strategy(HuffmanStrategy::Fixed);
+        }
         self
     }
 
@@ -219,6 +281,8 @@ impl Options {
         if let Deflaters::Libdeflater { compression } = &mut self.deflate {
             *compression = 12;
         }
+        self.alpha.insert(AlphaOptim::Black);
+        self.alpha.insert(AlphaOptim::White);
         self.apply_preset_3()
     }
 
@@ -237,7 +301,19 @@ impl Options {
     fn apply_preset_6(mut self) -> Self {
         self.filter.insert(RowFilter::Average);
         self.filter.insert(RowFilter::Paeth);
-        self.apply_preset_5()
+        self.alpha.insert(AlphaOptim::Up);
+        self.alpha.insert(AlphaOptim::Down);
+        self.alpha.insert(AlphaOptim::Left);
+        self.alpha.insert(AlphaOptim::Right);
+        let mut opts = self.apply_preset_5();
+        // Favor ratio at the highest preset: always pay for dynamic Huffman
+        // blocks and large blocks to maximize cross-row match opportunities.
+        // Same RustDeflate-only guard as apply_preset_0, for the same reason.
+        if matches!(opts.deflate, Deflaters::RustDeflate { .. }) {
+            opts.deflate.set_block_size(1 << 22);
+            opts.deflate.set_huffman_strategy(HuffmanStrategy::Dynamic);
+        }
+        opts
     }
 }
 
@@ -249,7 +325,7 @@ impl Default for Options {
             force: false,
             filter: indexset! {RowFilter::None, RowFilter::Sub, RowFilter::Entropy, RowFilter::Bigrams},
             interlace: Some(Interlacing::None),
-            optimize_alpha: false,
+            alpha: AlphaOptim::set_from_bool(false),
             bit_depth_reduction: true,
             color_type_reduction: true,
             palette_reduction: true,
@@ -260,6 +336,8 @@ impl Default for Options {
             deflate: Deflaters::Libdeflater { compression: 11 },
             fast_evaluation: true,
             timeout: None,
+            icc_to_chrm_gama: false,
+            limits: Limits::default(),
         }
     }
 }
@@ -19,7 +19,7 @@ use crate::{
     filters::*,
     headers::*,
     interlace::{deinterlace_image, interlace_image, Interlacing},
-    Options,
+    Deflaters, Limits, Options,
 };
 
 pub(crate) mod scan_lines;
@@ -118,6 +118,19 @@ impl PngData {
                     key_chunks.insert(chunk.name, chunk.data.to_owned());
                 }
                 _ if opts.strip.keep(&chunk.name) => {
+                    if chunk.name == *b"caBX" {
+                        if let StripChunks::Jumbf(keep_types) = &opts.strip {
+                            // Surgically keep only the requested JUMBF content
+                            // types instead of keeping-or-dropping the chunk wholesale
+                            if let Some(data) = rewrite_jumbf(chunk.data, keep_types) {
+                                aux_chunks.push(Chunk {
+                                    name: chunk.name,
+                                    data,
+                                });
+                            }
+                            continue;
+                        }
+                    }
                     if chunk.is_c2pa() {
                         // StripChunks::None is the default value, so to keep optimizing by default,
                         // interpret it as stripping the C2PA metadata.
@@ -174,7 +187,14 @@ impl PngData {
             key_chunks.remove(b"tRNS"),
         )?;
 
-        let raw = PngImage::new(ihdr, &idat_data)?;
+        // Reject implausibly large images before we ever try to allocate
+        // space for their decompressed data.
+        let pixels = u64::from(ihdr.width) * u64::from(ihdr.height);
+        if pixels > opts.limits.max_pixels {
+            return Err(PngError::LimitsExceeded);
+        }
+
+        let raw = PngImage::new(ihdr, &idat_data, &opts.limits)?;
 
         // Return the PngData
         Ok(Self {
@@ -185,6 +205,399 @@ impl PngData {
         })
     }
 
+    /// Create a new `PngData` struct by reading chunks one at a time from a [`Read`] source
+    ///
+    /// Unlike [`PngData::new`]/[`PngData::from_slice`], this never buffers
+    /// the whole encoded file: each chunk's `length + name + data + crc` is
+    /// read directly off `reader`, so a `Vec` sized to the entire input never
+    /// needs to exist at once. `IDAT`/`fdAT` payloads still have to be
+    /// concatenated into one contiguous buffer before they can be inflated,
+    /// so peak memory is roughly one copy of the compressed image data plus
+    /// one copy of the decoded image, rather than a full copy of the encoded
+    /// file *in addition to* those -- worthwhile when reading from a pipe or
+    /// socket, which can't be re-read from the start the way [`PngData::new`]
+    /// re-reads a file path. [`IhdrData::raw_data_size`] lets a caller
+    /// pre-size its own buffers from just the dimensions, without waiting for
+    /// the rest of the image to arrive.
+    ///
+    /// The [`Limits`] in `opts` are enforced the same way as in
+    /// [`PngData::from_slice`]: pixel count is checked as soon as `IHDR` is
+    /// parsed, and the decompressed size is checked before inflating. Since
+    /// each `IDAT`/`fdAT` chunk arrives (and is bounds-checked) separately
+    /// here, the running total of each concatenated buffer is also checked
+    /// as it grows, so many chunks individually under the per-chunk limit
+    /// can't still add up to an unbounded buffer.
+    pub fn from_reader<R: Read>(mut reader: R, opts: &Options) -> Result<Self, PngError> {
+        let mut header = [0; 8];
+        reader
+            .read_exact(&mut header)
+            .map_err(|_| PngError::new("Not a PNG file: too small"))?;
+        if !file_header_is_valid(&header) {
+            return Err(PngError::NotPNG);
+        }
+
+        let mut idat_data: Vec<u8> = Vec::new();
+        let mut key_chunks: FxHashMap<[u8; 4], Vec<u8>> = FxHashMap::default();
+        let mut aux_chunks: Vec<Chunk> = Vec::new();
+        let mut frames: Vec<Frame> = Vec::new();
+        let mut sequence_number = 0;
+        let mut checked_pixels = false;
+
+        loop {
+            let mut len_and_name = [0; 8];
+            if reader.read_exact(&mut len_and_name).is_err() {
+                return Err(PngError::TruncatedData);
+            }
+            let length = read_be_u32(&len_and_name[0..4]) as usize;
+            let name: [u8; 4] = len_and_name[4..8].try_into().unwrap();
+
+            // Unlike `from_slice`, there's no already-buffered file to bound
+            // this chunk's length by, so a claimed length has to be checked
+            // against a limit before we allocate space for it.
+            if length > opts.limits.max_decompressed_bytes {
+                return Err(PngError::LimitsExceeded);
+            }
+
+            if name == *b"IEND" {
+                // Still need to consume IEND's (always empty) CRC.
+                let mut crc = [0; 4];
+                reader
+                    .read_exact(&mut crc)
+                    .map_err(|_| PngError::TruncatedData)?;
+                break;
+            }
+
+            let mut data = vec![0; length];
+            reader
+                .read_exact(&mut data)
+                .map_err(|_| PngError::TruncatedData)?;
+            let mut crc_bytes = [0; 4];
+            reader
+                .read_exact(&mut crc_bytes)
+                .map_err(|_| PngError::TruncatedData)?;
+
+            if !opts.fix_errors {
+                let mut chunk_bytes = Vec::with_capacity(4 + data.len());
+                chunk_bytes.extend_from_slice(&name);
+                chunk_bytes.extend_from_slice(&data);
+                if deflate::crc32(&chunk_bytes) != read_be_u32(&crc_bytes) {
+                    return Err(PngError::new(&format!(
+                        "CRC Mismatch in {} chunk; May be recoverable by using --fix",
+                        String::from_utf8_lossy(&name)
+                    )));
+                }
+            }
+
+            match &name {
+                b"IDAT" => {
+                    if idat_data.is_empty() {
+                        aux_chunks.push(Chunk {
+                            name,
+                            data: Vec::new(),
+                        });
+                    }
+                    idat_data.extend_from_slice(&data);
+                    // Each chunk's claimed length is bounded above (line
+                    // ~253), but many chunks individually under that bound
+                    // can still add up to an unbounded total, so the
+                    // running concatenation is checked too.
+                    if idat_data.len() > opts.limits.max_decompressed_bytes {
+                        return Err(PngError::LimitsExceeded);
+                    }
+                }
+                b"IHDR" | b"PLTE" | b"tRNS" => {
+                    if name == *b"IHDR" && !checked_pixels {
+                        // We don't have PLTE/tRNS yet, but they don't affect
+                        // width/height, so the pixel-count limit can be
+                        // checked as soon as IHDR itself arrives.
+                        let width = read_be_u32(&data[0..4]);
+                        let height = read_be_u32(&data[4..8]);
+                        if u64::from(width) * u64::from(height) > opts.limits.max_pixels {
+                            return Err(PngError::LimitsExceeded);
+                        }
+                        checked_pixels = true;
+                    }
+                    key_chunks.insert(name, data);
+                }
+                _ if opts.strip.keep(&name) => {
+                    let raw = RawChunk {
+                        name,
+                        data: &data,
+                    };
+                    if name == *b"caBX" {
+                        if let StripChunks::Jumbf(keep_types) = &opts.strip {
+                            if let Some(rewritten) = rewrite_jumbf(&data, keep_types) {
+                                aux_chunks.push(Chunk {
+                                    name,
+                                    data: rewritten,
+                                });
+                            }
+                            continue;
+                        }
+                    }
+                    if raw.is_c2pa() {
+                        if opts.strip == StripChunks::None {
+                            continue;
+                        }
+                        return Err(PngError::C2PAMetadataPreventsChanges);
+                    }
+                    if name == *b"fcTL" || name == *b"fdAT" {
+                        if read_be_u32(&data[0..4]) != sequence_number {
+                            return Err(PngError::APNGOutOfOrder);
+                        }
+                        sequence_number += 1;
+                        if name == *b"fcTL" && !idat_data.is_empty() {
+                            frames.push(Frame::from_fctl_data(&data)?);
+                            continue;
+                        } else if name == *b"fdAT" {
+                            let frame = frames.last_mut().ok_or(PngError::APNGOutOfOrder)?;
+                            frame.data.extend_from_slice(&data[4..]);
+                            // Same running-total check as IDAT above: no
+                            // single fdAT chunk can exceed the limit, but
+                            // a run of them concatenated into one frame
+                            // could.
+                            if frame.data.len() > opts.limits.max_decompressed_bytes {
+                                return Err(PngError::LimitsExceeded);
+                            }
+                            continue;
+                        }
+                    }
+                    aux_chunks.push(Chunk { name, data });
+                }
+                b"acTL" => {
+                    warn!("Stripping animation data from APNG - image will become standard PNG")
+                }
+                _ => (),
+            }
+        }
+
+        if idat_data.is_empty() {
+            return Err(PngError::ChunkMissing("IDAT"));
+        }
+        let ihdr_chunk = match key_chunks.remove(b"IHDR") {
+            Some(ihdr) => ihdr,
+            None => return Err(PngError::ChunkMissing("IHDR")),
+        };
+        let ihdr = parse_ihdr_chunk(
+            &ihdr_chunk,
+            key_chunks.remove(b"PLTE"),
+            key_chunks.remove(b"tRNS"),
+        )?;
+
+        let raw = PngImage::new(ihdr, &idat_data, &opts.limits)?;
+
+        Ok(Self {
+            idat_data,
+            raw: Arc::new(raw),
+            aux_chunks,
+            frames,
+        })
+    }
+
+    /// Recompress each APNG frame's `fdAT` payload with `deflate`, keeping
+    /// whichever of the original or recompressed bytes is smaller.
+    ///
+    /// Unlike the default image, this only re-deflates the existing
+    /// bytestream; the decoded pixels are not re-filtered.
+    pub fn recompress_frames(&mut self, deflate: Deflaters) {
+        let main_ihdr = self.raw.ihdr.clone();
+        for frame in &mut self.frames {
+            // A frame inflates to the raw size of its own rectangle
+            let max_size = IhdrData {
+                width: frame.width,
+                height: frame.height,
+                ..main_ihdr.clone()
+            }
+            .raw_data_size();
+            let Ok(raw) = deflate::inflate(&frame.data, max_size) else {
+                continue;
+            };
+            if let Ok(recompressed) = deflate.deflate(&raw, Some(frame.data.len())) {
+                frame.data = recompressed;
+            }
+        }
+    }
+
+    /// Shrink each APNG frame after the first down to the minimal rectangle
+    /// that actually changed from the canvas left behind by the frame
+    /// before it, rewriting the frame's `fcTL` offset/size to match and
+    /// re-filtering and re-compressing just that sub-image.
+    ///
+    /// A frame whose changed rectangle turns out to be empty is identical
+    /// to what the canvas already showed; its delay is folded into the
+    /// previous frame and it is dropped entirely. The first frame is always
+    /// left full-size, since there is no prior canvas to diff it against.
+    ///
+    /// `dispose_op` is picked by looking at whether the following frame's
+    /// (possibly also just-shrunk) rectangle fully covers this one -- if it
+    /// does, nothing needs to be cleaned up first, otherwise the frame is
+    /// disposed back to what the canvas showed before it. This never
+    /// chooses `Background`, since that requires knowing the region should
+    /// become transparent rather than simply reverting, which isn't
+    /// something a byte-level diff against the previous canvas can tell us.
+    ///
+    /// Frames are composited and diffed as whole bytes, so only 8- and
+    /// 16-bit images are handled; APNGs with a sub-byte bit depth are left
+    /// untouched.
+    ///
+    /// The canvas used to diff each frame against is advanced according to
+    /// that frame's own (original) `dispose_op`, not the `dispose_op` this
+    /// function assigns it afterwards -- a frame that disposes to
+    /// `Background` or `Previous` leaves the canvas looking different than
+    /// what was just drawn, and the next frame must be diffed against that.
+    pub fn optimize_apng_frames(&mut self, opts: &Options) -> Result<(), PngError> {
+        if self.frames.len() < 2 || (self.raw.ihdr.bit_depth as u8) < 8 {
+            return Ok(());
+        }
+
+        let main_ihdr = self.raw.ihdr.clone();
+        let bpp = self.raw.channels_per_pixel() * self.raw.bytes_per_channel();
+        let bytes_per_channel = self.raw.bytes_per_channel();
+        let has_alpha = self.raw.ihdr.color_type.has_alpha();
+        let canvas_w = self.raw.ihdr.width as usize;
+        let canvas_h = self.raw.ihdr.height as usize;
+
+        let mut canvas = vec![0u8; canvas_w * canvas_h * bpp];
+        let mut new_frames: Vec<Frame> = Vec::with_capacity(self.frames.len());
+
+        for (i, frame) in std::mem::take(&mut self.frames).into_iter().enumerate() {
+            let raw = decode_apng_frame(&frame, &main_ihdr)?;
+            let (fx, fy, fw, fh) = (
+                frame.x_offset as usize,
+                frame.y_offset as usize,
+                frame.width as usize,
+                frame.height as usize,
+            );
+            // The frame's own disposal says what the canvas looks like once
+            // it's done being shown, i.e. what the *next* frame will be
+            // diffed against -- it has nothing to do with the (possibly
+            // different) `dispose_op` this function assigns further down
+            // based on the shrunk rectangles.
+            let orig_dispose_op = frame.dispose_op;
+
+            if i == 0 {
+                // Always kept full-size; composite it so later frames have
+                // a canvas to diff against.
+                blit(
+                    &mut canvas,
+                    canvas_w,
+                    &raw,
+                    fw,
+                    fh,
+                    fx,
+                    fy,
+                    bpp,
+                    bytes_per_channel,
+                    has_alpha,
+                    BlendOp::Source,
+                );
+                dispose_rect(&mut canvas, canvas_w, fx, fy, fw, fh, bpp, orig_dispose_op, None);
+                new_frames.push(frame);
+                continue;
+            }
+
+            let before = extract_rect(&canvas, canvas_w, fx, fy, fw, fh, bpp);
+            let mut after = before.clone();
+            blit(
+                &mut after,
+                fw,
+                &raw,
+                fw,
+                fh,
+                0,
+                0,
+                bpp,
+                bytes_per_channel,
+                has_alpha,
+                frame.blend_op,
+            );
+
+            write_rect(&mut canvas, canvas_w, &after, fx, fy, fw, fh, bpp);
+            dispose_rect(
+                &mut canvas,
+                canvas_w,
+                fx,
+                fy,
+                fw,
+                fh,
+                bpp,
+                orig_dispose_op,
+                Some(&before),
+            );
+
+            let Some((dx, dy, dw, dh)) = bounding_diff(&before, &after, fw, fh, bpp) else {
+                // Identical to what the canvas already showed: fold its
+                // delay into the previous frame instead of keeping it.
+                if let Some(prev) = new_frames.last_mut() {
+                    let (num, den) =
+                        add_delay((prev.delay_num, prev.delay_den), (frame.delay_num, frame.delay_den));
+                    prev.delay_num = num;
+                    prev.delay_den = den;
+                }
+                continue;
+            };
+
+            let cropped = extract_rect(&raw, fw, dx, dy, dw, dh, bpp);
+            let blend_op = if has_alpha && has_transparency(&cropped, bpp, bytes_per_channel) {
+                BlendOp::Over
+            } else {
+                BlendOp::Source
+            };
+
+            let sub = PngImage {
+                ihdr: IhdrData {
+                    width: dw as u32,
+                    height: dh as u32,
+                    ..main_ihdr.clone()
+                },
+                data: cropped,
+            };
+            let filtered = sub.filter_image(RowFilter::Paeth, false);
+            let compressed = opts.deflate.deflate(&filtered, None)?;
+
+            new_frames.push(Frame {
+                x_offset: frame.x_offset + dx as u32,
+                y_offset: frame.y_offset + dy as u32,
+                width: dw as u32,
+                height: dh as u32,
+                blend_op,
+                dispose_op: DisposeOp::None,
+                data: compressed,
+                ..frame
+            });
+        }
+
+        // Frame 0 is always left full-size/unshrunk (see the doc comment
+        // above), so `next_covers` below would almost always be false for
+        // it and hand it `DisposeOp::Previous` -- which the APNG spec
+        // forbids for the first frame, since there's no previous frame to
+        // restore to. Leave it at whatever its original `fcTL` specified by
+        // starting this loop at 1.
+        for i in 1..new_frames.len().saturating_sub(1) {
+            let (ax, ay, aw, ah) = (
+                new_frames[i].x_offset,
+                new_frames[i].y_offset,
+                new_frames[i].width,
+                new_frames[i].height,
+            );
+            let (bx, by, bw, bh) = (
+                new_frames[i + 1].x_offset,
+                new_frames[i + 1].y_offset,
+                new_frames[i + 1].width,
+                new_frames[i + 1].height,
+            );
+            let next_covers = bx <= ax && by <= ay && bx + bw >= ax + aw && by + bh >= ay + ah;
+            new_frames[i].dispose_op = if next_covers {
+                DisposeOp::None
+            } else {
+                DisposeOp::Previous
+            };
+        }
+
+        self.frames = new_frames;
+        Ok(())
+    }
+
     /// Format the `PngData` struct into a valid PNG bytestream
     #[must_use]
     pub fn output(&self) -> Vec<u8> {
@@ -277,11 +690,19 @@ impl PngData {
 }
 
 impl PngImage {
-    pub fn new(ihdr: IhdrData, compressed_data: &[u8]) -> Result<Self, PngError> {
-        let raw_data = deflate::inflate(compressed_data, ihdr.raw_data_size())?;
+    pub fn new(
+        ihdr: IhdrData,
+        compressed_data: &[u8],
+        limits: &Limits,
+    ) -> Result<Self, PngError> {
+        let raw_data_size = ihdr.raw_data_size();
+        if raw_data_size > limits.max_decompressed_bytes {
+            return Err(PngError::LimitsExceeded);
+        }
+        let raw_data = deflate::inflate(compressed_data, raw_data_size)?;
 
         // Reject files with incorrect width/height or truncated data
-        if raw_data.len() != ihdr.raw_data_size() {
+        if raw_data.len() != raw_data_size {
             return Err(PngError::TruncatedData);
         }
 
@@ -551,6 +972,267 @@ impl PngImage {
         }
         filtered
     }
+
+    /// Select per-row filters for the whole image with a genetic algorithm
+    ///
+    /// The heuristic strategies in [`Self::filter_image`] (`MinSum`,
+    /// `Entropy`, `Bigrams`, `BigEnt`) each pick a row's filter greedily and
+    /// independently, which is locally optimal but ignores how one row's
+    /// choice affects how well the *next* row compresses. This instead
+    /// treats the image's whole sequence of per-row filter choices as a
+    /// genome and searches it with a genetic algorithm, the same approach
+    /// [pngwolf](https://bjoern.hoehrmann.de/pngwolf/) uses: fitness of a
+    /// genome is the size of the image filtered according to it and then run
+    /// through the same fast level-1 `zlib_compress` estimator the `Brute`
+    /// strategy already relies on, lower is better.
+    ///
+    /// Since `Up`/`Average`/`Paeth` only ever look at the *raw* previous
+    /// scanline, never at how that scanline was itself filtered, a row's
+    /// candidate output for each of the 5 standard filters can be
+    /// precomputed once up front rather than per fitness evaluation. The
+    /// population is seeded with the all-`None` and all-`Paeth` genomes, the
+    /// `MinSum`/`Entropy` heuristic results, and the rest filled with random
+    /// genomes; each generation keeps the top [`GeneticFilterParams::elite_count`]
+    /// genomes unchanged and fills the rest via uniform crossover (each row
+    /// copied from one of two randomly chosen parents) plus mutation
+    /// (reassigning a small fraction of rows to a random filter). As with
+    /// `filter_image`, the first row of each interlacing pass is restricted
+    /// to `None`/`Sub`.
+    #[must_use]
+    pub fn filter_image_genetic(&self, params: GeneticFilterParams, optimize_alpha: bool) -> Vec<u8> {
+        let lines: Vec<_> = self.scan_lines(false).collect();
+        if lines.is_empty() {
+            return Vec::new();
+        }
+        let bpp = self.bytes_per_channel() * self.channels_per_pixel();
+        let alpha_bytes = if optimize_alpha && self.ihdr.color_type.has_alpha() {
+            self.bytes_per_channel()
+        } else {
+            0
+        };
+
+        // Per line, per filter type: the filtered bytes, filter type byte
+        // included as the first element (matching `filter_line`'s output).
+        let mut candidates: Vec<[Vec<u8>; 5]> = Vec::with_capacity(lines.len());
+        // Rows restricted to `None`/`Sub`: the first row of each pass.
+        let mut single_line_only: Vec<bool> = Vec::with_capacity(lines.len());
+        let mut prev_line: Vec<u8> = Vec::new();
+        let mut prev_pass: Option<u8> = None;
+        let mut f_buf = Vec::new();
+        for line in &lines {
+            let is_first_of_pass = prev_pass != line.pass;
+            if is_first_of_pass || line.data.len() != prev_line.len() {
+                prev_line = vec![0; line.data.len()];
+            }
+            single_line_only.push(is_first_of_pass);
+
+            let mut row: [Vec<u8>; 5] = Default::default();
+            let filters: &[RowFilter] = if is_first_of_pass {
+                &RowFilter::SINGLE_LINE[..]
+            } else {
+                &RowFilter::STANDARD[..]
+            };
+            for &f in filters {
+                let mut line_data = line.data.to_vec();
+                f.filter_line(bpp, &mut line_data, &prev_line, &mut f_buf, alpha_bytes);
+                row[f as usize] = f_buf.clone();
+            }
+            if is_first_of_pass {
+                // Every genome slot must be indexable even on restricted
+                // rows; fall back to `None`'s output for the rest, matching
+                // `filter_image`'s own fallback for fixed filters here.
+                for f in [RowFilter::Up, RowFilter::Average, RowFilter::Paeth] {
+                    row[f as usize] = row[RowFilter::None as usize].clone();
+                }
+            }
+            candidates.push(row);
+            prev_line = line.data.to_vec();
+            prev_pass = line.pass;
+        }
+
+        let allowed = |i: usize| -> &'static [RowFilter] {
+            if single_line_only[i] {
+                &RowFilter::SINGLE_LINE[..]
+            } else {
+                &RowFilter::STANDARD[..]
+            }
+        };
+
+        let mut rng = GeneticRng::new(0x9E37_79B9_7F4A_7C15 ^ lines.len() as u64);
+
+        let mut population: Vec<Vec<u8>> = Vec::with_capacity(params.population_size.max(4));
+        population.push(vec![RowFilter::None as u8; lines.len()]);
+        population.push(
+            (0..lines.len())
+                .map(|i| {
+                    if single_line_only[i] {
+                        RowFilter::Sub as u8
+                    } else {
+                        RowFilter::Paeth as u8
+                    }
+                })
+                .collect(),
+        );
+        // MSAD (MinSum) and Shannon entropy (Entropy) heuristics, picked per
+        // line from the already-computed candidates -- see `filter_image`
+        // for the same scoring applied per line as it goes.
+        population.push(
+            (0..lines.len())
+                .map(|i| {
+                    allowed(i)
+                        .iter()
+                        .min_by_key(|&&f| {
+                            candidates[i][f as usize]
+                                .iter()
+                                .fold(0usize, |acc, &x| acc + (x as i8).unsigned_abs() as usize)
+                        })
+                        .copied()
+                        .unwrap_or(RowFilter::None) as u8
+                })
+                .collect(),
+        );
+        population.push(
+            (0..lines.len())
+                .map(|i| {
+                    allowed(i)
+                        .iter()
+                        .max_by_key(|&&f| {
+                            let mut counts = [0u32; 256];
+                            for &b in &candidates[i][f as usize] {
+                                counts[b as usize] += 1;
+                            }
+                            counts
+                                .iter()
+                                .fold(0i32, |acc, &x| if x == 0 { acc } else { acc + ilog2i(x) as i32 })
+                        })
+                        .copied()
+                        .unwrap_or(RowFilter::None) as u8
+                })
+                .collect(),
+        );
+        while population.len() < params.population_size {
+            let genome = (0..lines.len())
+                .map(|i| {
+                    let filters = allowed(i);
+                    filters[rng.below(filters.len())] as u8
+                })
+                .collect();
+            population.push(genome);
+        }
+
+        let assemble = |genome: &[u8]| -> Vec<u8> {
+            let mut out = Vec::with_capacity(self.data.len() + lines.len());
+            for (i, &gene) in genome.iter().enumerate() {
+                out.extend_from_slice(&candidates[i][gene as usize]);
+            }
+            out
+        };
+
+        let mut compressor = Compressor::new(CompressionLvl::new(BRUTE_LEVEL).unwrap());
+        let mut best_genome = population[0].clone();
+        let mut best_fitness = usize::MAX;
+        let mut stalled = 0;
+
+        for _generation in 0..params.max_generations {
+            let mut scored: Vec<(usize, Vec<u8>)> = population
+                .drain(..)
+                .map(|genome| {
+                    let filtered = assemble(&genome);
+                    let capacity = compressor.zlib_compress_bound(filtered.len());
+                    let mut dest = vec![0; capacity];
+                    let size = compressor
+                        .zlib_compress(&filtered, &mut dest)
+                        .unwrap_or(usize::MAX);
+                    (size, genome)
+                })
+                .collect();
+            scored.sort_by_key(|(size, _)| *size);
+
+            if scored[0].0 < best_fitness {
+                best_fitness = scored[0].0;
+                best_genome.clone_from(&scored[0].1);
+                stalled = 0;
+            } else {
+                stalled += 1;
+                if stalled >= params.stall_generations {
+                    break;
+                }
+            }
+
+            let elite_count = params.elite_count.max(1).min(scored.len());
+            population.extend(scored[..elite_count].iter().map(|(_, genome)| genome.clone()));
+            while population.len() < params.population_size {
+                let parent_a = &scored[rng.below(scored.len())].1;
+                let parent_b = &scored[rng.below(scored.len())].1;
+                let mut child: Vec<u8> = (0..lines.len())
+                    .map(|i| if rng.below(2) == 0 { parent_a[i] } else { parent_b[i] })
+                    .collect();
+                for (i, gene) in child.iter_mut().enumerate() {
+                    if rng.below_f32() < params.mutation_rate {
+                        let filters = allowed(i);
+                        *gene = filters[rng.below(filters.len())] as u8;
+                    }
+                }
+                population.push(child);
+            }
+        }
+
+        assemble(&best_genome)
+    }
+}
+
+/// Parameters controlling [`PngImage::filter_image_genetic`]
+#[derive(Clone, Copy, Debug)]
+pub struct GeneticFilterParams {
+    /// Number of candidate genomes kept alive each generation
+    pub population_size: usize,
+    /// Upper bound on the number of generations to run
+    pub max_generations: u32,
+    /// Stop early once this many generations in a row fail to improve on the best fitness seen
+    pub stall_generations: u32,
+    /// Number of top genomes carried over unchanged into the next generation
+    pub elite_count: usize,
+    /// Fraction (0.0-1.0) of rows mutated to a random filter in each offspring
+    pub mutation_rate: f32,
+}
+
+impl Default for GeneticFilterParams {
+    fn default() -> Self {
+        Self {
+            population_size: 20,
+            max_generations: 30,
+            stall_generations: 8,
+            elite_count: 2,
+            mutation_rate: 0.05,
+        }
+    }
+}
+
+/// Minimal xorshift64 PRNG so the genetic filter search doesn't need an
+/// external RNG dependency; not suitable for anything beyond this use.
+struct GeneticRng(u64);
+
+impl GeneticRng {
+    fn new(seed: u64) -> Self {
+        Self(seed | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    fn below_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u32 << 24) as f32
+    }
 }
 
 fn write_png_block(key: &[u8], chunk: &[u8], output: &mut Vec<u8>) {
@@ -569,3 +1251,270 @@ const fn ilog2i(i: u32) -> u32 {
     let log = 32 - i.leading_zeros() - 1;
     i * log + ((i - (1 << log)) << 1)
 }
+
+/// Decode an APNG frame's `fdAT` payload into raw, unfiltered pixel bytes
+/// sized to its own `width`/`height` (which may differ from the main
+/// image's), reusing the main image's color type/bit depth/interlacing --
+/// all APNG frames share those with the default image, only the frame
+/// rectangle itself varies.
+fn decode_apng_frame(frame: &Frame, main_ihdr: &IhdrData) -> Result<Vec<u8>, PngError> {
+    let ihdr = IhdrData {
+        width: frame.width,
+        height: frame.height,
+        ..main_ihdr.clone()
+    };
+    let max_size = ihdr.raw_data_size() * 2 + 1000;
+    let inflated = deflate::inflate(&frame.data, max_size)?;
+    let sub = PngImage { ihdr, data: inflated };
+    sub.unfilter_image()
+}
+
+fn pixel_channel(pixel: &[u8], channel: usize, bytes_per_channel: usize) -> u32 {
+    let start = channel * bytes_per_channel;
+    if bytes_per_channel == 2 {
+        u32::from(u16::from_be_bytes([pixel[start], pixel[start + 1]]))
+    } else {
+        u32::from(pixel[start])
+    }
+}
+
+fn write_pixel_channel(pixel: &mut [u8], channel: usize, bytes_per_channel: usize, value: u32) {
+    let start = channel * bytes_per_channel;
+    if bytes_per_channel == 2 {
+        pixel[start..start + 2].copy_from_slice(&(value as u16).to_be_bytes());
+    } else {
+        pixel[start] = value as u8;
+    }
+}
+
+/// Copy `src` (sized `src_w`x`src_h`) onto `dst` (of row stride `dst_w`) at
+/// `(x, y)`, either overwriting it outright (`BlendOp::Source`, also used
+/// whenever the image has no alpha channel to blend with) or alpha-blending
+/// it on top (`BlendOp::Over`).
+#[allow(clippy::too_many_arguments)]
+fn blit(
+    dst: &mut [u8],
+    dst_w: usize,
+    src: &[u8],
+    src_w: usize,
+    src_h: usize,
+    x: usize,
+    y: usize,
+    bpp: usize,
+    bytes_per_channel: usize,
+    has_alpha: bool,
+    blend_op: BlendOp,
+) {
+    let channels = bpp / bytes_per_channel;
+    let max_value = if bytes_per_channel == 2 { 65535.0 } else { 255.0 };
+    for row in 0..src_h {
+        let src_row = &src[row * src_w * bpp..(row + 1) * src_w * bpp];
+        let dst_start = (y + row) * dst_w * bpp + x * bpp;
+        let dst_row = &mut dst[dst_start..dst_start + src_w * bpp];
+        if !has_alpha || blend_op == BlendOp::Source {
+            dst_row.copy_from_slice(src_row);
+            continue;
+        }
+        for px in 0..src_w {
+            let src_px = &src_row[px * bpp..(px + 1) * bpp];
+            let dst_px = &mut dst_row[px * bpp..(px + 1) * bpp];
+            let src_a = pixel_channel(src_px, channels - 1, bytes_per_channel) as f32 / max_value;
+            if src_a >= 1.0 {
+                dst_px.copy_from_slice(src_px);
+                continue;
+            }
+            if src_a <= 0.0 {
+                continue;
+            }
+            let dst_a = pixel_channel(dst_px, channels - 1, bytes_per_channel) as f32 / max_value;
+            let out_a = src_a + dst_a * (1.0 - src_a);
+            for c in 0..channels - 1 {
+                let src_c = pixel_channel(src_px, c, bytes_per_channel) as f32;
+                let dst_c = pixel_channel(dst_px, c, bytes_per_channel) as f32;
+                let out_c = if out_a > 0.0 {
+                    (src_c * src_a + dst_c * dst_a * (1.0 - src_a)) / out_a
+                } else {
+                    0.0
+                };
+                write_pixel_channel(dst_px, c, bytes_per_channel, out_c.round() as u32);
+            }
+            write_pixel_channel(dst_px, channels - 1, bytes_per_channel, (out_a * max_value).round() as u32);
+        }
+    }
+}
+
+/// Copy a `w`x`h` rectangle at `(x, y)` out of a row-major buffer of stride
+/// `stride_w`.
+fn extract_rect(data: &[u8], stride_w: usize, x: usize, y: usize, w: usize, h: usize, bpp: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(w * h * bpp);
+    for row in 0..h {
+        let start = (y + row) * stride_w * bpp + x * bpp;
+        out.extend_from_slice(&data[start..start + w * bpp]);
+    }
+    out
+}
+
+/// Inverse of [`extract_rect`]: write a tightly-packed `w`x`h` rectangle
+/// back into a row-major buffer of stride `stride_w` at `(x, y)`.
+fn write_rect(data: &mut [u8], stride_w: usize, rect: &[u8], x: usize, y: usize, w: usize, h: usize, bpp: usize) {
+    for row in 0..h {
+        let start = (y + row) * stride_w * bpp + x * bpp;
+        data[start..start + w * bpp].copy_from_slice(&rect[row * w * bpp..(row + 1) * w * bpp]);
+    }
+}
+
+/// Advance `canvas` to what it looks like once a frame has finished being
+/// shown and is disposed of per its original `dispose_op`, so the *next*
+/// frame's diff is taken against the right starting point.
+///
+/// `DisposeOp::None` leaves the just-composited pixels in place (the caller
+/// is expected to have already written them in via [`write_rect`]).
+/// `DisposeOp::Background` clears the rectangle back to fully transparent
+/// black. `DisposeOp::Previous` restores whatever the rectangle held before
+/// this frame was composited; per the APNG spec this is only meaningful
+/// when there *was* a previous frame, so a missing `before` (the first
+/// frame) falls back to `None` semantics.
+fn dispose_rect(
+    canvas: &mut [u8],
+    stride_w: usize,
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    bpp: usize,
+    dispose_op: DisposeOp,
+    before: Option<&[u8]>,
+) {
+    match dispose_op {
+        DisposeOp::None => {}
+        DisposeOp::Background => {
+            let blank = vec![0u8; w * h * bpp];
+            write_rect(canvas, stride_w, &blank, x, y, w, h, bpp);
+        }
+        DisposeOp::Previous => {
+            if let Some(before) = before {
+                write_rect(canvas, stride_w, before, x, y, w, h, bpp);
+            }
+        }
+    }
+}
+
+/// Return the minimal bounding rectangle of pixels that differ between two
+/// `w`x`h` buffers, or `None` if they're identical.
+fn bounding_diff(before: &[u8], after: &[u8], w: usize, h: usize, bpp: usize) -> Option<(usize, usize, usize, usize)> {
+    let (mut min_x, mut max_x, mut min_y, mut max_y) = (w, 0, h, 0);
+    let mut changed = false;
+    for row in 0..h {
+        for col in 0..w {
+            let start = (row * w + col) * bpp;
+            if before[start..start + bpp] != after[start..start + bpp] {
+                changed = true;
+                min_x = min_x.min(col);
+                max_x = max_x.max(col);
+                min_y = min_y.min(row);
+                max_y = max_y.max(row);
+            }
+        }
+    }
+    changed.then_some((min_x, min_y, max_x - min_x + 1, max_y - min_y + 1))
+}
+
+/// Returns `true` if any pixel's alpha channel is less than fully opaque
+fn has_transparency(data: &[u8], bpp: usize, bytes_per_channel: usize) -> bool {
+    let channels = bpp / bytes_per_channel;
+    let max_value = if bytes_per_channel == 2 { 65535 } else { 255 };
+    data.chunks_exact(bpp)
+        .any(|pixel| pixel_channel(pixel, channels - 1, bytes_per_channel) < max_value)
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Add two `fcTL` delay fractions together, clamping the result back into
+/// `u16` range if the common denominator would overflow it.
+fn add_delay(prev: (u16, u16), cur: (u16, u16)) -> (u16, u16) {
+    // A denominator of 0 means 100 per the APNG spec.
+    let pd = u32::from(if prev.1 == 0 { 100 } else { prev.1 });
+    let cd = u32::from(if cur.1 == 0 { 100 } else { cur.1 });
+    let g = gcd(pd, cd);
+    let lcm = pd / g * cd;
+    let mut num = u32::from(prev.0) * (lcm / pd) + u32::from(cur.0) * (lcm / cd);
+    let mut den = lcm;
+    while (num > u32::from(u16::MAX) || den > u32::from(u16::MAX)) && den > 1 {
+        num /= 2;
+        den /= 2;
+    }
+    (num.min(u32::from(u16::MAX)) as u16, den.max(1) as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounding_diff_finds_expected_rectangle() {
+        // 4x4, 1 byte/pixel, only the 2x2 block at (1,1)..(3,3) changes.
+        let before = vec![0u8; 16];
+        let mut after = before.clone();
+        for y in 1..3 {
+            for x in 1..3 {
+                after[y * 4 + x] = 0xFF;
+            }
+        }
+        assert_eq!(bounding_diff(&before, &after, 4, 4, 1), Some((1, 1, 2, 2)));
+    }
+
+    #[test]
+    fn bounding_diff_none_when_identical() {
+        let buf = vec![1u8, 2, 3, 4];
+        assert_eq!(bounding_diff(&buf, &buf, 2, 2, 1), None);
+    }
+
+    // Regression test for the canvas-advancement bug: disposing a frame must
+    // leave the canvas looking the way its own `dispose_op` says it will,
+    // not the way `DisposeOp::None` would -- otherwise the *next* frame's
+    // diff is taken against pixels that were never actually on screen.
+    #[test]
+    fn dispose_rect_background_clears_for_next_frames_diff() {
+        let stride = 4;
+        let mut canvas = vec![0u8; 4 * 4];
+        let drawn = vec![0xFFu8; 2 * 2];
+        write_rect(&mut canvas, stride, &drawn, 1, 1, 2, 2, 1);
+        assert_eq!(extract_rect(&canvas, stride, 1, 1, 2, 2, 1), vec![0xFF; 4]);
+
+        dispose_rect(&mut canvas, stride, 1, 1, 2, 2, 1, DisposeOp::Background, None);
+
+        assert_eq!(extract_rect(&canvas, stride, 1, 1, 2, 2, 1), vec![0; 4]);
+    }
+
+    #[test]
+    fn dispose_rect_previous_restores_prior_canvas() {
+        let stride = 4;
+        let mut canvas = vec![0u8; 4 * 4];
+        let before = vec![7u8; 2 * 2];
+        write_rect(&mut canvas, stride, &before, 1, 1, 2, 2, 1);
+        let drawn = vec![0xFFu8; 2 * 2];
+        write_rect(&mut canvas, stride, &drawn, 1, 1, 2, 2, 1);
+
+        dispose_rect(&mut canvas, stride, 1, 1, 2, 2, 1, DisposeOp::Previous, Some(&before));
+
+        assert_eq!(extract_rect(&canvas, stride, 1, 1, 2, 2, 1), vec![7; 4]);
+    }
+
+    #[test]
+    fn dispose_rect_none_leaves_composited_pixels() {
+        let stride = 4;
+        let mut canvas = vec![0u8; 4 * 4];
+        let drawn = vec![0xFFu8; 2 * 2];
+        write_rect(&mut canvas, stride, &drawn, 1, 1, 2, 2, 1);
+
+        dispose_rect(&mut canvas, stride, 1, 1, 2, 2, 1, DisposeOp::None, None);
+
+        assert_eq!(extract_rect(&canvas, stride, 1, 1, 2, 2, 1), vec![0xFF; 4]);
+    }
+}
@@ -0,0 +1,132 @@
+use indexmap::{indexset, IndexSet};
+
+use crate::{colors::ColorType, png::PngImage};
+
+/// Strategies for rewriting the RGB components of fully-transparent pixels.
+///
+/// A fully-transparent pixel's color is invisible, so oxipng is free to pick
+/// whatever RGB value best helps the filter/deflate stages that follow. Each
+/// variant is tried as a scratch candidate (see
+/// [`Options::alpha`][crate::Options::alpha]) and the smallest result wins,
+/// the same way individual [`RowFilter`][crate::RowFilter] strategies compete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlphaOptim {
+    /// Leave transparent pixels untouched
+    NoOp,
+    /// Set transparent pixels to black (0, 0, 0)
+    Black,
+    /// Set transparent pixels to white (255, 255, 255)
+    White,
+    /// Copy the RGB of the nearest opaque-or-already-assigned pixel above
+    Up,
+    /// Copy the RGB of the nearest opaque-or-already-assigned pixel below
+    Down,
+    /// Copy the RGB of the nearest opaque-or-already-assigned pixel to the left
+    Left,
+    /// Copy the RGB of the nearest opaque-or-already-assigned pixel to the right
+    Right,
+}
+
+impl AlphaOptim {
+    /// Build the set of strategies equivalent to the old `optimize_alpha: bool` flag:
+    /// just [`NoOp`][Self::NoOp] when disabled, or the classic heuristics when enabled.
+    #[must_use]
+    pub fn set_from_bool(enabled: bool) -> IndexSet<Self> {
+        if enabled {
+            indexset! {Self::NoOp, Self::Black, Self::White, Self::Up, Self::Down, Self::Left, Self::Right}
+        } else {
+            indexset! {Self::NoOp}
+        }
+    }
+
+    /// Apply this strategy to a scratch copy of `png`, returning `None` for [`NoOp`][Self::NoOp]
+    /// or if the image has no alpha channel to optimize.
+    #[must_use]
+    pub fn apply(self, png: &PngImage) -> Option<PngImage> {
+        if self == Self::NoOp || !png.ihdr.color_type.has_alpha() {
+            return None;
+        }
+
+        let channels = png.channels_per_pixel();
+        let bpc = png.bytes_per_channel();
+        let width = png.ihdr.width as usize;
+        let height = png.ihdr.height as usize;
+        let stride = channels * bpc * width;
+        let alpha_offset = (channels - 1) * bpc;
+
+        let mut data = png.data.clone();
+        let is_transparent = |data: &[u8], idx: usize| -> bool {
+            data[idx + alpha_offset..idx + alpha_offset + bpc]
+                .iter()
+                .all(|&b| b == 0)
+        };
+
+        match self {
+            Self::Black | Self::White => {
+                let fill = if self == Self::Black { 0 } else { 0xFF };
+                for row in 0..height {
+                    for col in 0..width {
+                        let idx = row * stride + col * channels * bpc;
+                        if is_transparent(&data, idx) {
+                            data[idx..idx + alpha_offset].fill(fill);
+                        }
+                    }
+                }
+            }
+            Self::Up | Self::Down => {
+                let rows: Box<dyn Iterator<Item = usize>> = if self == Self::Up {
+                    Box::new(1..height)
+                } else {
+                    Box::new((0..height.saturating_sub(1)).rev())
+                };
+                let prev_row = |row: usize| if self == Self::Up { row - 1 } else { row + 1 };
+                for row in rows {
+                    for col in 0..width {
+                        let idx = row * stride + col * channels * bpc;
+                        if is_transparent(&data, idx) {
+                            let src = prev_row(row) * stride + col * channels * bpc;
+                            let (lo, hi) = data.split_at_mut(idx);
+                            let src_slice = if src < idx {
+                                &lo[src..src + alpha_offset]
+                            } else {
+                                &hi[src - idx..src - idx + alpha_offset]
+                            };
+                            let copy: Vec<u8> = src_slice.to_vec();
+                            data[idx..idx + alpha_offset].copy_from_slice(&copy);
+                        }
+                    }
+                }
+            }
+            Self::Left | Self::Right => {
+                for row in 0..height {
+                    let cols: Box<dyn Iterator<Item = usize>> = if self == Self::Left {
+                        Box::new(1..width)
+                    } else {
+                        Box::new((0..width.saturating_sub(1)).rev())
+                    };
+                    for col in cols {
+                        let idx = row * stride + col * channels * bpc;
+                        if is_transparent(&data, idx) {
+                            let src_col = if self == Self::Left { col - 1 } else { col + 1 };
+                            let src = row * stride + src_col * channels * bpc;
+                            let copy: Vec<u8> = data[src..src + alpha_offset].to_vec();
+                            data[idx..idx + alpha_offset].copy_from_slice(&copy);
+                        }
+                    }
+                }
+            }
+            Self::NoOp => unreachable!(),
+        }
+
+        Some(PngImage {
+            ihdr: crate::headers::IhdrData {
+                color_type: match &png.ihdr.color_type {
+                    ColorType::RGBA | ColorType::GrayscaleAlpha => png.ihdr.color_type.clone(),
+                    other => other.clone(),
+                },
+                ..png.ihdr.clone()
+            },
+            data,
+        })
+    }
+}
@@ -0,0 +1,256 @@
+//! aarch64 NEON implementation of [`SIMD128`]/[`SIMD256`].
+//!
+//! `V256` has no native NEON type, so it's represented as a pair of `V128`s
+//! and every `v256_*` op falls back to the generic `split_merge`-based
+//! default on [`SIMD256`], same as every other non-AVX2 backend.
+
+#[cfg(target_arch = "aarch64")]
+use std::arch::aarch64::*;
+
+use crate::traits::{InstructionSet, SIMD128, SIMD256};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Neon(());
+
+unsafe impl InstructionSet for Neon {
+    #[inline(always)]
+    fn is_enabled() -> bool {
+        #[cfg(target_feature = "neon")]
+        {
+            true
+        }
+        #[cfg(not(target_feature = "neon"))]
+        {
+            #[cfg(all(feature = "detect", target_arch = "aarch64"))]
+            if std::arch::is_aarch64_feature_detected!("neon") {
+                return true;
+            }
+            false
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn new() -> Self {
+        Self(())
+    }
+}
+
+unsafe impl SIMD128 for Neon {
+    type V128 = uint8x16_t;
+
+    #[inline(always)]
+    unsafe fn v128_load(self, addr: *const u8) -> Self::V128 {
+        vld1q_u8(addr)
+    }
+
+    #[inline(always)]
+    unsafe fn v128_load_unaligned(self, addr: *const u8) -> Self::V128 {
+        vld1q_u8(addr)
+    }
+
+    #[inline(always)]
+    unsafe fn v128_store_unaligned(self, addr: *mut u8, a: Self::V128) {
+        vst1q_u8(addr, a)
+    }
+
+    #[inline(always)]
+    fn v128_or(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        unsafe { vorrq_u8(a, b) }
+    }
+
+    #[inline(always)]
+    fn v128_and(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        unsafe { vandq_u8(a, b) }
+    }
+
+    #[inline(always)]
+    fn v128_to_bytes(self, a: Self::V128) -> [u8; 16] {
+        unsafe { std::mem::transmute(a) }
+    }
+
+    #[inline(always)]
+    fn v128_create_zero(self) -> Self::V128 {
+        unsafe { vdupq_n_u8(0) }
+    }
+
+    #[inline(always)]
+    fn v128_all_zero(self, a: Self::V128) -> bool {
+        unsafe { vmaxvq_u8(a) == 0 }
+    }
+
+    #[inline(always)]
+    fn v128_andnot(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        unsafe { vandq_u8(a, vmvnq_u8(b)) }
+    }
+
+    #[inline(always)]
+    fn u8x16_splat(self, x: u8) -> Self::V128 {
+        unsafe { vdupq_n_u8(x) }
+    }
+
+    #[inline(always)]
+    fn u8x16_swizzle(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        unsafe { vqtbl1q_u8(a, b) }
+    }
+
+    #[inline(always)]
+    fn u8x16_add(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        unsafe { vaddq_u8(a, b) }
+    }
+
+    #[inline(always)]
+    fn u8x16_sub(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        unsafe { vsubq_u8(a, b) }
+    }
+
+    #[inline(always)]
+    fn u8x16_sub_sat(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        unsafe { vqsubq_u8(a, b) }
+    }
+
+    #[inline(always)]
+    fn u8x16_any_zero(self, a: Self::V128) -> bool {
+        unsafe { vminvq_u8(a) == 0 }
+    }
+
+    #[inline(always)]
+    fn u8x16_min(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        unsafe { vminq_u8(a, b) }
+    }
+
+    #[inline(always)]
+    fn i8x16_splat(self, x: i8) -> Self::V128 {
+        unsafe { vreinterpretq_u8_s8(vdupq_n_s8(x)) }
+    }
+
+    #[inline(always)]
+    fn i8x16_cmp_lt(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        unsafe {
+            vreinterpretq_u8_u8(vcltq_s8(vreinterpretq_s8_u8(a), vreinterpretq_s8_u8(b)))
+        }
+    }
+
+    #[inline(always)]
+    fn i8x16_cmp_eq(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        unsafe { vceqq_s8(vreinterpretq_s8_u8(a), vreinterpretq_s8_u8(b)) }
+    }
+
+    #[inline(always)]
+    fn u16x8_shl<const IMM8: i32>(self, a: Self::V128) -> Self::V128 {
+        unsafe {
+            vreinterpretq_u8_u16(vshlq_n_u16::<IMM8>(vreinterpretq_u16_u8(a)))
+        }
+    }
+
+    #[inline(always)]
+    fn u16x8_shr<const IMM8: i32>(self, a: Self::V128) -> Self::V128 {
+        unsafe {
+            vreinterpretq_u8_u16(vshrq_n_u16::<IMM8>(vreinterpretq_u16_u8(a)))
+        }
+    }
+
+    #[inline(always)]
+    fn u16x8_splat(self, x: u16) -> Self::V128 {
+        unsafe { vreinterpretq_u8_u16(vdupq_n_u16(x)) }
+    }
+
+    #[inline(always)]
+    fn u32x4_splat(self, x: u32) -> Self::V128 {
+        unsafe { vreinterpretq_u8_u32(vdupq_n_u32(x)) }
+    }
+
+    #[inline(always)]
+    fn u32x4_shl<const IMM8: i32>(self, a: Self::V128) -> Self::V128 {
+        unsafe {
+            vreinterpretq_u8_u32(vshlq_n_u32::<IMM8>(vreinterpretq_u32_u8(a)))
+        }
+    }
+
+    #[inline(always)]
+    fn u32x4_shr<const IMM8: i32>(self, a: Self::V128) -> Self::V128 {
+        unsafe {
+            vreinterpretq_u8_u32(vshrq_n_u32::<IMM8>(vreinterpretq_u32_u8(a)))
+        }
+    }
+
+    #[inline(always)]
+    fn u16x8_to_f32x4x2(self, a: Self::V128) -> (Self::V128, Self::V128) {
+        unsafe {
+            let u16s = vreinterpretq_u16_u8(a);
+            let lo = vcvtq_f32_u32(vmovl_u16(vget_low_u16(u16s)));
+            let hi = vcvtq_f32_u32(vmovl_u16(vget_high_u16(u16s)));
+            (vreinterpretq_u8_f32(lo), vreinterpretq_u8_f32(hi))
+        }
+    }
+
+    #[inline(always)]
+    fn f32x4_mul(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        unsafe {
+            vreinterpretq_u8_f32(vmulq_f32(vreinterpretq_f32_u8(a), vreinterpretq_f32_u8(b)))
+        }
+    }
+
+    #[inline(always)]
+    fn f32x4_round_to_i32(self, a: Self::V128) -> Self::V128 {
+        unsafe {
+            vreinterpretq_u8_s32(vcvtnq_s32_f32(vreinterpretq_f32_u8(a)))
+        }
+    }
+
+    #[inline(always)]
+    fn i32x4x2_to_u8x16(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        // Only the low 8 lanes are meaningful (4 from `a` + 4 from `b`); the
+        // high 8 are a duplicate of the low 8 and unused by callers that only
+        // process 8 samples (one `u16x8`) per `V128`.
+        unsafe {
+            let a = vreinterpretq_s32_u8(a);
+            let b = vreinterpretq_s32_u8(b);
+            let au16 = vqmovun_s32(a);
+            let bu16 = vqmovun_s32(b);
+            let narrowed = vqmovn_u16(vcombine_u16(au16, bu16));
+            vcombine_u8(narrowed, narrowed)
+        }
+    }
+}
+
+unsafe impl SIMD256 for Neon {
+    // No native 256-bit NEON register; represent V256 as a pair of V128s and
+    // inherit every v256_* default from the generic split_merge helper.
+    type V256 = (uint8x16_t, uint8x16_t);
+
+    #[inline(always)]
+    fn v256_from_v128x2(self, a: Self::V128, b: Self::V128) -> Self::V256 {
+        (a, b)
+    }
+
+    #[inline(always)]
+    fn v256_to_v128x2(self, a: Self::V256) -> (Self::V128, Self::V128) {
+        a
+    }
+
+    #[inline(always)]
+    fn v256_to_bytes(self, a: Self::V256) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        out[..16].copy_from_slice(&self.v128_to_bytes(a.0));
+        out[16..].copy_from_slice(&self.v128_to_bytes(a.1));
+        out
+    }
+
+    #[inline(always)]
+    fn u16x16_from_u8x16(self, a: Self::V128) -> Self::V256 {
+        unsafe {
+            let lo = vmovl_u8(vget_low_u8(a));
+            let hi = vmovl_u8(vget_high_u8(a));
+            (vreinterpretq_u8_u16(lo), vreinterpretq_u8_u16(hi))
+        }
+    }
+
+    #[inline(always)]
+    fn u64x4_unzip_low(self, a: Self::V256) -> Self::V128 {
+        unsafe {
+            let a0 = vreinterpretq_u64_u8(a.0);
+            let a1 = vreinterpretq_u64_u8(a.1);
+            vreinterpretq_u8_u64(vtrn1q_u64(a0, a1))
+        }
+    }
+}
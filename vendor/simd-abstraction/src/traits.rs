@@ -26,6 +26,11 @@ unsafe impl InstructionSet for Fallback {
     }
 }
 
+// Concrete per-arch `InstructionSet`/`SIMD128`/`SIMD256` implementations live
+// in sibling modules: `x86` for SSE2/AVX2, `neon` for aarch64, and `wasm` for
+// `wasm32` simd128. Only the 128-bit primitives are implemented per-arch;
+// every `v256_*` method is inherited from the generic `split_merge`-based
+// default on `SIMD256` unless a backend has a genuine native 256-bit type.
 #[allow(unused_macros)]
 macro_rules! define_isa {
     ($ty:ident, $feature: tt, $detect: tt) => {
@@ -90,6 +95,80 @@ pub unsafe trait SIMD128: InstructionSet {
     fn u32x4_splat(self, x: u32) -> Self::V128;
     fn u32x4_shl<const IMM8: i32>(self, a: Self::V128) -> Self::V128;
     fn u32x4_shr<const IMM8: i32>(self, a: Self::V128) -> Self::V128;
+
+    /// Widen the low 4 and high 4 lanes of a `u16x8` to two `f32x4`
+    ///
+    /// The default implementation round-trips through [`Self::v128_to_bytes`] /
+    /// [`Self::v128_load_unaligned`], so it works on every backend without
+    /// per-arch code; override it with native `cvt`-style intrinsics for speed.
+    #[inline(always)]
+    fn u16x8_to_f32x4x2(self, a: Self::V128) -> (Self::V128, Self::V128) {
+        let bytes = self.v128_to_bytes(a);
+        let lane = |i: usize| u16::from_le_bytes([bytes[i * 2], bytes[i * 2 + 1]]);
+        let pack = |vals: [u16; 4]| -> Self::V128 {
+            let mut buf = [0u8; 16];
+            for (i, &v) in vals.iter().enumerate() {
+                buf[i * 4..i * 4 + 4].copy_from_slice(&(f32::from(v)).to_le_bytes());
+            }
+            unsafe { self.v128_load_unaligned(buf.as_ptr()) }
+        };
+        (
+            pack([lane(0), lane(1), lane(2), lane(3)]),
+            pack([lane(4), lane(5), lane(6), lane(7)]),
+        )
+    }
+
+    /// Multiply two `f32x4` vectors lane-wise
+    #[inline(always)]
+    fn f32x4_mul(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        let ab = self.v128_to_bytes(a);
+        let bb = self.v128_to_bytes(b);
+        let mut out = [0u8; 16];
+        for i in 0..4 {
+            let av = f32::from_le_bytes(ab[i * 4..i * 4 + 4].try_into().unwrap());
+            let bv = f32::from_le_bytes(bb[i * 4..i * 4 + 4].try_into().unwrap());
+            out[i * 4..i * 4 + 4].copy_from_slice(&(av * bv).to_le_bytes());
+        }
+        unsafe { self.v128_load_unaligned(out.as_ptr()) }
+    }
+
+    /// Round each `f32x4` lane to the nearest integer (ties to even), stored as `i32x4`
+    #[inline(always)]
+    fn f32x4_round_to_i32(self, a: Self::V128) -> Self::V128 {
+        let ab = self.v128_to_bytes(a);
+        let mut out = [0u8; 16];
+        for i in 0..4 {
+            let v = f32::from_le_bytes(ab[i * 4..i * 4 + 4].try_into().unwrap());
+            out[i * 4..i * 4 + 4].copy_from_slice(&(round_ties_even(v) as i32).to_le_bytes());
+        }
+        unsafe { self.v128_load_unaligned(out.as_ptr()) }
+    }
+
+    /// Narrow two `i32x4` vectors into one `u8x16`, with unsigned saturation
+    #[inline(always)]
+    fn i32x4x2_to_u8x16(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        let ab = self.v128_to_bytes(a);
+        let bb = self.v128_to_bytes(b);
+        let mut out = [0u8; 16];
+        for (i, bytes) in [ab, bb].iter().enumerate() {
+            for lane in 0..4 {
+                let v = i32::from_le_bytes(bytes[lane * 4..lane * 4 + 4].try_into().unwrap());
+                out[i * 4 + lane] = v.clamp(0, 255) as u8;
+            }
+        }
+        unsafe { self.v128_load_unaligned(out.as_ptr()) }
+    }
+}
+
+/// Round-half-to-even, matching the behavior of SSE2's `cvtps2dq` rounding mode
+#[inline(always)]
+fn round_ties_even(v: f32) -> f32 {
+    let rounded = v.round();
+    if (v - v.trunc()).abs() == 0.5 && (rounded as i64) % 2 != 0 {
+        rounded - v.signum()
+    } else {
+        rounded
+    }
 }
 
 #[inline(always)]
@@ -0,0 +1,213 @@
+//! `wasm32` SIMD128 implementation of [`SIMD128`]/[`SIMD256`].
+//!
+//! Like [`Neon`][crate::neon::Neon], there's no native 256-bit vector type in
+//! the wasm SIMD proposal, so `V256` is a pair of `v128`s and the `v256_*`
+//! methods all use the generic `split_merge` default from [`SIMD256`].
+
+#[cfg(target_arch = "wasm32")]
+use std::arch::wasm32::*;
+
+use crate::traits::{InstructionSet, SIMD128, SIMD256};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Wasm128(());
+
+unsafe impl InstructionSet for Wasm128 {
+    #[inline(always)]
+    fn is_enabled() -> bool {
+        // simd128 has no runtime-detection story on the web today; it's either
+        // enabled at compile time for the whole module or not available at all.
+        cfg!(target_feature = "simd128")
+    }
+
+    #[inline(always)]
+    unsafe fn new() -> Self {
+        Self(())
+    }
+}
+
+unsafe impl SIMD128 for Wasm128 {
+    type V128 = v128;
+
+    #[inline(always)]
+    unsafe fn v128_load(self, addr: *const u8) -> Self::V128 {
+        v128_load(addr.cast())
+    }
+
+    #[inline(always)]
+    unsafe fn v128_load_unaligned(self, addr: *const u8) -> Self::V128 {
+        v128_load(addr.cast())
+    }
+
+    #[inline(always)]
+    unsafe fn v128_store_unaligned(self, addr: *mut u8, a: Self::V128) {
+        v128_store(addr.cast(), a)
+    }
+
+    #[inline(always)]
+    fn v128_or(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        v128_or(a, b)
+    }
+
+    #[inline(always)]
+    fn v128_and(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        v128_and(a, b)
+    }
+
+    #[inline(always)]
+    fn v128_to_bytes(self, a: Self::V128) -> [u8; 16] {
+        unsafe { std::mem::transmute(a) }
+    }
+
+    #[inline(always)]
+    fn v128_create_zero(self) -> Self::V128 {
+        u8x16_splat(0)
+    }
+
+    #[inline(always)]
+    fn v128_all_zero(self, a: Self::V128) -> bool {
+        !v128_any_true(a)
+    }
+
+    #[inline(always)]
+    fn v128_andnot(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        v128_andnot(a, b)
+    }
+
+    #[inline(always)]
+    fn u8x16_splat(self, x: u8) -> Self::V128 {
+        u8x16_splat(x)
+    }
+
+    #[inline(always)]
+    fn u8x16_swizzle(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        u8x16_swizzle(a, b)
+    }
+
+    #[inline(always)]
+    fn u8x16_add(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        u8x16_add(a, b)
+    }
+
+    #[inline(always)]
+    fn u8x16_sub(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        u8x16_sub(a, b)
+    }
+
+    #[inline(always)]
+    fn u8x16_sub_sat(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        u8x16_sub_sat(a, b)
+    }
+
+    #[inline(always)]
+    fn u8x16_any_zero(self, a: Self::V128) -> bool {
+        v128_any_true(u8x16_eq(a, u8x16_splat(0)))
+    }
+
+    #[inline(always)]
+    fn u8x16_min(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        u8x16_min(a, b)
+    }
+
+    #[inline(always)]
+    fn i8x16_splat(self, x: i8) -> Self::V128 {
+        i8x16_splat(x)
+    }
+
+    #[inline(always)]
+    fn i8x16_cmp_lt(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        i8x16_lt(a, b)
+    }
+
+    #[inline(always)]
+    fn i8x16_cmp_eq(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        i8x16_eq(a, b)
+    }
+
+    #[inline(always)]
+    fn u16x8_shl<const IMM8: i32>(self, a: Self::V128) -> Self::V128 {
+        u16x8_shl(a, IMM8 as u32)
+    }
+
+    #[inline(always)]
+    fn u16x8_shr<const IMM8: i32>(self, a: Self::V128) -> Self::V128 {
+        u16x8_shr(a, IMM8 as u32)
+    }
+
+    #[inline(always)]
+    fn u16x8_splat(self, x: u16) -> Self::V128 {
+        u16x8_splat(x)
+    }
+
+    #[inline(always)]
+    fn u32x4_splat(self, x: u32) -> Self::V128 {
+        u32x4_splat(x)
+    }
+
+    #[inline(always)]
+    fn u32x4_shl<const IMM8: i32>(self, a: Self::V128) -> Self::V128 {
+        u32x4_shl(a, IMM8 as u32)
+    }
+
+    #[inline(always)]
+    fn u32x4_shr<const IMM8: i32>(self, a: Self::V128) -> Self::V128 {
+        u32x4_shr(a, IMM8 as u32)
+    }
+
+    #[inline(always)]
+    fn u16x8_to_f32x4x2(self, a: Self::V128) -> (Self::V128, Self::V128) {
+        let lo = u32x4_extend_low_u16x8(a);
+        let hi = u32x4_extend_high_u16x8(a);
+        (f32x4_convert_u32x4(lo), f32x4_convert_u32x4(hi))
+    }
+
+    #[inline(always)]
+    fn f32x4_mul(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        f32x4_mul(a, b)
+    }
+
+    #[inline(always)]
+    fn f32x4_round_to_i32(self, a: Self::V128) -> Self::V128 {
+        i32x4_trunc_sat_f32x4(f32x4_nearest(a))
+    }
+
+    #[inline(always)]
+    fn i32x4x2_to_u8x16(self, a: Self::V128, b: Self::V128) -> Self::V128 {
+        // As with the NEON backend, only the low 8 lanes (4 from `a` + 4 from
+        // `b`) are meaningful; the high 8 duplicate them.
+        let narrow = i16x8_narrow_i32x4(a, b);
+        u8x16_narrow_i16x8(narrow, narrow)
+    }
+}
+
+unsafe impl SIMD256 for Wasm128 {
+    type V256 = (v128, v128);
+
+    #[inline(always)]
+    fn v256_from_v128x2(self, a: Self::V128, b: Self::V128) -> Self::V256 {
+        (a, b)
+    }
+
+    #[inline(always)]
+    fn v256_to_v128x2(self, a: Self::V256) -> (Self::V128, Self::V128) {
+        a
+    }
+
+    #[inline(always)]
+    fn v256_to_bytes(self, a: Self::V256) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        out[..16].copy_from_slice(&self.v128_to_bytes(a.0));
+        out[16..].copy_from_slice(&self.v128_to_bytes(a.1));
+        out
+    }
+
+    #[inline(always)]
+    fn u16x16_from_u8x16(self, a: Self::V128) -> Self::V256 {
+        (u16x8_extend_low_u8x16(a), u16x8_extend_high_u8x16(a))
+    }
+
+    #[inline(always)]
+    fn u64x4_unzip_low(self, a: Self::V256) -> Self::V128 {
+        u64x2_shuffle::<0, 2>(a.0, a.1)
+    }
+}